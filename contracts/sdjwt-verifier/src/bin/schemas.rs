@@ -1,12 +1,12 @@
 use cosmwasm_schema::write_api;
 
-use avida_common::types::AvidaVerifierExecuteMsg;
-use avida_sdjwt_verifier::msg::{InstantiateMsg, QueryMsg};
+use avida_sdjwt_verifier::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
-        execute: AvidaVerifierExecuteMsg,
+        execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -0,0 +1,181 @@
+use avida_cheqd::types::ResourceReqPacket;
+use cosmwasm_std::{
+    ensure, DepsMut, Env, Event, IbcBasicResponse, IbcChannel, IbcChannelCloseMsg,
+    IbcChannelConnectMsg, MessageInfo, Order, Response, StdResult, Storage, Timestamp,
+};
+
+use crate::{
+    errors::SdjwtVerifierError,
+    fetch::{dispatch_queued_requests, record_fetch_failure, MAX_RETRY_LIMIT},
+    registry::ensure_owner,
+    state::{
+        CHANNEL_ID, CHEQD_CHANNELS, PENDING_VERIFICATION_REQ_REQUESTS, QUEUED_RESOURCE_REQUESTS,
+    },
+    types::{CheqdChannel, CheqdChannelsResponse},
+};
+
+/// The channel the resource requests are sent on, `None` before a channel is connected
+/// or once the active channel is closed
+pub fn active_channel(storage: &dyn Storage) -> StdResult<Option<String>> {
+    let Some(channel_id) = CHANNEL_ID.may_load(storage)? else {
+        return Ok(None);
+    };
+    let closed = CHEQD_CHANNELS
+        .may_load(storage, &channel_id)?
+        .is_some_and(|channel| channel.closed_at.is_some());
+    Ok((!closed).then_some(channel_id))
+}
+
+/// Records a connected channel to cheqd. The first one becomes the active channel and the requests
+/// queued until then are sent, at most `MAX_RETRY_LIMIT`, the others with `RetryPendingFetches`.
+/// Later channels are only used once the owner switches to them with `SetCheqdChannel`
+pub fn ibc_channel_connect_handler(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, SdjwtVerifierError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+    save_channel(deps.storage, msg.channel(), None)?;
+
+    let res = IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_connect")
+        .add_attribute("channel_id", &channel_id);
+    if CHANNEL_ID.may_load(deps.storage)?.is_some() {
+        return Ok(res.add_attribute("active", "false"));
+    }
+
+    CHANNEL_ID.save(deps.storage, &channel_id)?;
+    let msgs = dispatch_queued_requests(deps.storage, &env, MAX_RETRY_LIMIT as usize)?;
+    Ok(res
+        .add_attribute("active", "true")
+        .add_attribute("dispatched", msgs.len().to_string())
+        .add_submessages(msgs))
+}
+
+/// Records a closed channel to cheqd, which can no longer be used.
+/// When it is the active channel, its requests in flight are recorded as failed fetches
+/// and new requests are queued until the owner switches to another channel
+pub fn ibc_channel_close_handler(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, SdjwtVerifierError> {
+    let channel_id = msg.channel().endpoint.channel_id.clone();
+    let was_active = active_channel(deps.storage)?.as_ref() == Some(&channel_id);
+    save_channel(deps.storage, msg.channel(), Some(env.block.time))?;
+
+    let mut res = IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_close")
+        .add_attribute("channel_id", &channel_id);
+    if was_active {
+        let events = fail_in_flight_requests(deps.storage, &env, "IBC channel closed")?;
+        res = res.add_events(events);
+    }
+    Ok(res)
+}
+
+/// Switches the resource requests to another open channel to cheqd (owner only).
+/// The requests in flight on the previous channel are recorded as failed fetches,
+/// to be retried on the new one, and the queued requests are sent on it
+pub fn handle_set_cheqd_channel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_id: String,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_owner(deps.storage, &info.sender)?;
+
+    let channel = CHEQD_CHANNELS
+        .may_load(deps.storage, &channel_id)?
+        .ok_or_else(|| SdjwtVerifierError::ChannelNotFound(channel_id.clone()))?;
+    ensure!(
+        channel.closed_at.is_none(),
+        SdjwtVerifierError::ChannelClosed(channel_id)
+    );
+    ensure!(
+        active_channel(deps.storage)?.as_ref() != Some(&channel_id),
+        SdjwtVerifierError::ChannelAlreadyActive(channel_id)
+    );
+
+    // The acks of the previous channel are no longer handled
+    let events = fail_in_flight_requests(deps.storage, &env, "IBC channel replaced")?;
+    CHANNEL_ID.save(deps.storage, &channel_id)?;
+    let msgs = dispatch_queued_requests(deps.storage, &env, MAX_RETRY_LIMIT as usize)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_cheqd_channel")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("dispatched", msgs.len().to_string())
+        .add_submessages(msgs)
+        .add_events(events))
+}
+
+/// Whether a packet was sent on the active channel, the acks and timeouts
+/// of the packets sent on a replaced or closed channel are ignored
+pub fn is_active_channel(storage: &dyn Storage, channel_id: &str) -> StdResult<bool> {
+    Ok(active_channel(storage)?.as_deref() == Some(channel_id))
+}
+
+pub fn query_cheqd_channels(storage: &dyn Storage) -> StdResult<CheqdChannelsResponse> {
+    Ok(CheqdChannelsResponse {
+        active: active_channel(storage)?,
+        channels: CHEQD_CHANNELS
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, channel)| channel))
+            .collect::<StdResult<_>>()?,
+    })
+}
+
+/// Saves a channel to cheqd, closed at `closed_at` if given
+fn save_channel(
+    storage: &mut dyn Storage,
+    channel: &IbcChannel,
+    closed_at: Option<Timestamp>,
+) -> StdResult<()> {
+    CHEQD_CHANNELS.save(
+        storage,
+        &channel.endpoint.channel_id,
+        &CheqdChannel {
+            channel_id: channel.endpoint.channel_id.clone(),
+            connection_id: channel.connection_id.clone(),
+            counterparty_channel_id: channel.counterparty_endpoint.channel_id.clone(),
+            closed_at,
+        },
+    )
+}
+
+/// Records the requests in flight on the active channel as failed fetches of their routes,
+/// the queued requests, which were not sent yet, are kept
+fn fail_in_flight_requests(
+    storage: &mut dyn Storage,
+    env: &Env,
+    reason: &str,
+) -> StdResult<Vec<Event>> {
+    let pending = PENDING_VERIFICATION_REQ_REQUESTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut events = vec![];
+    for (resource_key, subscribers) in pending {
+        if QUEUED_RESOURCE_REQUESTS.has(storage, &resource_key) {
+            continue;
+        }
+        PENDING_VERIFICATION_REQ_REQUESTS.remove(storage, &resource_key);
+        // The resource is keyed by `collection_id:resource_id`
+        let (collection_id, resource_id) = resource_key.split_once(':').unwrap_or_default();
+        let resource_req = ResourceReqPacket {
+            resource_id: resource_id.to_string(),
+            collection_id: collection_id.to_string(),
+        };
+        for pending_route in subscribers {
+            events.push(record_fetch_failure(
+                storage,
+                env,
+                resource_req.clone(),
+                pending_route,
+                reason.to_string(),
+            )?);
+        }
+    }
+    Ok(events)
+}
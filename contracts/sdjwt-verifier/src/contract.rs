@@ -7,39 +7,74 @@ use cosmwasm_std::{
     IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, MessageInfo, Response, StdAck,
     StdResult,
 };
-use cw2::set_contract_version;
+use cw2::{ensure_from_older_version, get_contract_version, set_contract_version};
 
 use crate::{
+    channel::{
+        handle_set_cheqd_channel, ibc_channel_close_handler, ibc_channel_connect_handler,
+        query_cheqd_channels,
+    },
     errors::SdjwtVerifierError,
-    msg::{InstantiateMsg, QueryMsg},
+    fetch::{
+        handle_refresh_issuer_keys, handle_retry_pending_fetches, migrate_pending_requests,
+        query_fetch_failures, query_pending_fetches, query_route_key_ids, query_route_key_versions,
+        query_route_status,
+    },
+    msg::{
+        ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SdjwtVerifierExecuteMsg,
+        SdjwtVerifierSudoMsg, SudoMsg,
+    },
+    registry::*,
+    revocation::{
+        handle_create_shared_revocation_list, handle_update_shared_revocation_list,
+        handle_update_shared_revocation_list_acl, is_credential_revoked, is_revoked,
+        is_revoked_in_shared_list, query_revocation_delegates, query_revocation_list,
+        query_revocation_roots, query_shared_revocation_list, query_shared_revocation_list_entries,
+        revocation_updates,
+    },
+    state::{CHEQD_NETWORK, OWNER},
+    status_list::query_status_list,
+    types::KeyCompromise,
     verifier::*,
 };
 
-use avida_cheqd::ibc::{ibc_channel_close_handler, ibc_channel_open_handler};
+use avida_cheqd::ibc::ibc_channel_open_handler;
 
 // Contract name and version info
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The `(major, minor)` version storing the route revocations outside of the requirements
+/// and the subscribed routes of the in flight requests
+const STORAGE_MIGRATION_VERSION: (u64, u64) = (0, 2);
 
 // Entry points
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, SdjwtVerifierError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    OWNER.save(deps.storage, &info.sender)?;
+
     MAX_PRESENTATION_LENGTH.save(deps.storage, &msg.max_presentation_len)?;
 
+    if let Some(network) = msg.cheqd_network {
+        CHEQD_NETWORK.save(deps.storage, &network)?;
+    }
+
+    // The cheqd fetches of the registrations are queued until the IBC channel is connected
+    let mut response = Response::default();
     for app in msg.init_registrations {
         let admin = deps.api.addr_validate(&app.app_admin)?;
         let app_addr = deps.api.addr_validate(&app.app_addr)?;
-        _register(deps.storage, &env, &admin, app_addr.as_str(), app.routes)?;
+        let registration = _register(deps.storage, &env, &admin, app_addr.as_str(), app.routes)?;
+        response = extend_response(response, registration);
     }
 
-    Ok(Response::default())
+    Ok(response)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -47,40 +82,156 @@ pub fn execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: AvidaVerifierExecuteMsg,
+    msg: ExecuteMsg,
 ) -> Result<Response, SdjwtVerifierError> {
     match msg {
-        AvidaVerifierExecuteMsg::UpdateRevocationList { app_addr, request } => {
-            handle_update_revocation_list(deps, info, app_addr, request)
-        }
-        AvidaVerifierExecuteMsg::Register { app_addr, requests } => {
-            handle_register(deps, env, info, app_addr, requests)
-        }
-        AvidaVerifierExecuteMsg::Verify {
-            presentation,
-            route_id,
-            app_addr,
-            additional_requirements,
-        } => handle_verify(
-            deps,
-            env,
-            info,
-            presentation,
-            route_id,
-            app_addr,
-            additional_requirements,
-        ),
-        AvidaVerifierExecuteMsg::Update {
-            app_addr,
-            route_id,
-            route_criteria,
-        } => handle_update(deps, env, info, app_addr, route_id, route_criteria),
-        AvidaVerifierExecuteMsg::Deregister { app_addr } => handle_deregister(deps, info, app_addr),
+        ExecuteMsg::Avida(msg) => match msg {
+            AvidaVerifierExecuteMsg::UpdateRevocationList { app_addr, request } => {
+                handle_update_revocation_list(deps, info, app_addr, request)
+            }
+            AvidaVerifierExecuteMsg::Register { app_addr, requests } => {
+                handle_register(deps, env, info, app_addr, requests)
+            }
+            AvidaVerifierExecuteMsg::Verify {
+                presentation,
+                route_id,
+                app_addr,
+                additional_requirements,
+            } => handle_verify(
+                deps,
+                env,
+                app_addr.unwrap_or_else(|| info.sender.to_string()),
+                route_id,
+                presentation,
+                additional_requirements,
+                None,
+            ),
+            AvidaVerifierExecuteMsg::Update {
+                app_addr,
+                route_id,
+                route_criteria,
+            } => handle_update(deps, env, info, app_addr, route_id, route_criteria),
+            AvidaVerifierExecuteMsg::Deregister { app_addr } => {
+                handle_deregister(deps, env, info, app_addr)
+            }
+        },
+        ExecuteMsg::SdjwtVerifier(msg) => match msg {
+            SdjwtVerifierExecuteMsg::VerifyWithRevocationProof {
+                presentation,
+                route_id,
+                app_addr,
+                additional_requirements,
+                revocation_proof,
+            } => handle_verify(
+                deps,
+                env,
+                app_addr.unwrap_or_else(|| info.sender.to_string()),
+                route_id,
+                presentation,
+                additional_requirements,
+                Some(revocation_proof),
+            ),
+            SdjwtVerifierExecuteMsg::SetTrustedIssuer {
+                name,
+                issuer_data,
+                metadata,
+            } => handle_set_trusted_issuer(deps, info, name, issuer_data, metadata),
+            SdjwtVerifierExecuteMsg::RemoveTrustedIssuer { name } => {
+                handle_remove_trusted_issuer(deps, info, name)
+            }
+            SdjwtVerifierExecuteMsg::UpdateOwner { new_owner } => {
+                handle_update_owner(deps, info, new_owner)
+            }
+            SdjwtVerifierExecuteMsg::SuspendIssuer {
+                iss,
+                key_thumbprint,
+                reason,
+            } => handle_suspend_issuer(deps, env, info, iss, key_thumbprint, reason),
+            SdjwtVerifierExecuteMsg::ReinstateIssuer {
+                iss,
+                key_thumbprint,
+            } => handle_reinstate_issuer(deps, info, iss, key_thumbprint),
+            SdjwtVerifierExecuteMsg::SetKeyCompromised {
+                app_addr,
+                key_thumbprint,
+                cutoff,
+                allowed_idx,
+            } => handle_set_key_compromised(
+                deps,
+                info,
+                app_addr,
+                key_thumbprint,
+                cutoff,
+                allowed_idx,
+            ),
+            SdjwtVerifierExecuteMsg::ClearKeyCompromised {
+                app_addr,
+                key_thumbprint,
+            } => handle_clear_key_compromised(deps, info, app_addr, key_thumbprint),
+            SdjwtVerifierExecuteMsg::UpdateStatusList {
+                app_addr,
+                route_id,
+                status_list,
+            } => handle_update_status_list(deps, env, info, app_addr, route_id, status_list),
+            SdjwtVerifierExecuteMsg::UpdateRevocationRoot {
+                app_addr,
+                route_id,
+                root,
+            } => handle_update_revocation_root(deps, env, info, app_addr, route_id, root),
+            SdjwtVerifierExecuteMsg::SetRevocationDelegate {
+                app_addr,
+                route_id,
+                delegate,
+                iss,
+            } => handle_set_revocation_delegate(deps, info, app_addr, route_id, delegate, iss),
+            SdjwtVerifierExecuteMsg::RemoveRevocationDelegate {
+                app_addr,
+                route_id,
+                delegate,
+            } => handle_remove_revocation_delegate(deps, info, app_addr, route_id, delegate),
+            SdjwtVerifierExecuteMsg::CreateSharedRevocationList { list_id, editors } => {
+                handle_create_shared_revocation_list(deps, info, list_id, editors)
+            }
+            SdjwtVerifierExecuteMsg::UpdateSharedRevocationListAcl {
+                list_id,
+                new_owner,
+                add_editors,
+                remove_editors,
+            } => handle_update_shared_revocation_list_acl(
+                deps,
+                info,
+                list_id,
+                new_owner,
+                add_editors,
+                remove_editors,
+            ),
+            SdjwtVerifierExecuteMsg::UpdateSharedRevocationList {
+                list_id,
+                revoke,
+                unrevoke,
+                revoke_ranges,
+                unrevoke_ranges,
+            } => handle_update_shared_revocation_list(
+                deps,
+                info,
+                list_id,
+                revocation_updates(revoke, unrevoke, revoke_ranges, unrevoke_ranges),
+            ),
+            SdjwtVerifierExecuteMsg::RetryPendingFetches { limit } => {
+                handle_retry_pending_fetches(deps, env, limit)
+            }
+            SdjwtVerifierExecuteMsg::RefreshIssuerKeys { app_addr, route_id } => {
+                handle_refresh_issuer_keys(deps, env, app_addr, route_id)
+            }
+            SdjwtVerifierExecuteMsg::SetCheqdChannel { channel_id } => {
+                handle_set_cheqd_channel(deps, env, info, channel_id)
+            }
+        },
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetRouteVerificationKey { app_addr, route_id } => {
             let route_verification_key = query_route_verification_keys(deps, app_addr, route_id)?;
@@ -98,45 +249,252 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             let route_requirements = query_route_requirements(deps, app_addr, route_id)?;
             to_json_binary(&route_requirements)
         }
-    }
-}
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn sudo(
-    deps: DepsMut,
-    env: Env,
-    msg: AvidaVerifierSudoMsg,
-) -> Result<Response, SdjwtVerifierError> {
-    match msg {
-        AvidaVerifierSudoMsg::Verify {
+        QueryMsg::GetOwner {} => to_json_binary(&query_owner(deps)?),
+        QueryMsg::GetTrustedIssuer { name } => to_json_binary(&query_trusted_issuer(deps, name)?),
+        QueryMsg::GetTrustedIssuers { start_after, limit } => {
+            let trusted_issuers = query_trusted_issuers(deps, start_after, limit)?;
+            to_json_binary(&trusted_issuers)
+        }
+        QueryMsg::GetSuspendedIssuers { start_after, limit } => {
+            let suspended_issuers = query_suspended_issuers(deps, start_after, limit)?;
+            to_json_binary(&suspended_issuers)
+        }
+        QueryMsg::GetSuspendedKeys { start_after, limit } => {
+            let suspended_keys = query_suspended_keys(deps, start_after, limit)?;
+            to_json_binary(&suspended_keys)
+        }
+        QueryMsg::RoutesByIssuer {
+            iss,
+            start_after,
+            limit,
+        } => {
+            let routes = query_routes_by_issuer(deps, iss, start_after, limit)?;
+            to_json_binary(&routes)
+        }
+        QueryMsg::GetStatusList {
             app_addr,
             route_id,
-            presentation,
-            additional_requirements,
-        } => handle_sudo_verify(
-            deps,
-            env,
+            iss,
+            uri,
+        } => to_json_binary(&query_status_list(deps, app_addr, route_id, iss, uri)?),
+        QueryMsg::GetCompromisedKeys {
+            app_addr,
+            start_after,
+            limit,
+        } => {
+            let compromised_keys = query_compromised_keys(deps, app_addr, start_after, limit)?;
+            to_json_binary(&compromised_keys)
+        }
+        QueryMsg::GetRevocationRoots { app_addr, route_id } => {
+            to_json_binary(&query_revocation_roots(deps.storage, app_addr, route_id)?)
+        }
+        QueryMsg::IsRevoked {
             app_addr,
             route_id,
-            presentation,
-            additional_requirements,
+            idx,
+            iss,
+        } => {
+            let revoked = match iss {
+                Some(iss) => is_credential_revoked(deps.storage, &app_addr, route_id, &iss, idx)?,
+                None => is_revoked(deps.storage, &app_addr, route_id, None, idx)?,
+            };
+            to_json_binary(&revoked)
+        }
+        QueryMsg::RevocationList {
+            app_addr,
+            route_id,
+            iss,
+            start_after,
+            limit,
+        } => {
+            let revoked =
+                query_revocation_list(deps.storage, app_addr, route_id, iss, start_after, limit)?;
+            to_json_binary(&revoked)
+        }
+        QueryMsg::RevocationDelegates { app_addr, route_id } => to_json_binary(
+            &query_revocation_delegates(deps.storage, app_addr, route_id)?,
         ),
-        AvidaVerifierSudoMsg::Update {
+        QueryMsg::GetSharedRevocationList { list_id } => {
+            to_json_binary(&query_shared_revocation_list(deps.storage, list_id)?)
+        }
+        QueryMsg::IsRevokedInSharedList { list_id, idx } => {
+            to_json_binary(&is_revoked_in_shared_list(deps.storage, &list_id, idx)?)
+        }
+        QueryMsg::SharedRevocationListEntries {
+            list_id,
+            start_after,
+            limit,
+        } => {
+            let revoked =
+                query_shared_revocation_list_entries(deps.storage, list_id, start_after, limit)?;
+            to_json_binary(&revoked)
+        }
+        QueryMsg::GetFetchFailures { app_addr, route_id } => {
+            to_json_binary(&query_fetch_failures(deps.storage, app_addr, route_id)?)
+        }
+        QueryMsg::RouteStatus { app_addr, route_id } => {
+            to_json_binary(&query_route_status(deps.storage, &env, app_addr, route_id)?)
+        }
+        QueryMsg::PendingFetches { start_after, limit } => {
+            to_json_binary(&query_pending_fetches(deps.storage, start_after, limit)?)
+        }
+        QueryMsg::RouteKeyVersions {
             app_addr,
             route_id,
-            route_criteria,
-        } => handle_sudo_update(deps, env, app_addr, route_id, route_criteria),
-        AvidaVerifierSudoMsg::Register {
+            height,
+        } => to_json_binary(&query_route_key_versions(
+            deps.storage,
             app_addr,
-            app_admin,
-            routes,
-        } => {
-            let admin = deps.api.addr_validate(&app_admin)?;
-            _register(deps.storage, &env, &admin, &app_addr, routes)
+            route_id,
+            height,
+        )?),
+        QueryMsg::RouteKeyIds { app_addr, route_id } => {
+            to_json_binary(&query_route_key_ids(deps.storage, app_addr, route_id)?)
         }
+        QueryMsg::CheqdChannels {} => to_json_binary(&query_cheqd_channels(deps.storage)?),
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, SdjwtVerifierError> {
+    match msg {
+        SudoMsg::Avida(msg) => match msg {
+            AvidaVerifierSudoMsg::Verify {
+                app_addr,
+                route_id,
+                presentation,
+                additional_requirements,
+            } => handle_sudo_verify(
+                deps,
+                env,
+                app_addr,
+                route_id,
+                presentation,
+                additional_requirements,
+                None,
+            ),
+            AvidaVerifierSudoMsg::Update {
+                app_addr,
+                route_id,
+                route_criteria,
+            } => handle_sudo_update(deps, env, app_addr, route_id, route_criteria),
+            AvidaVerifierSudoMsg::Register {
+                app_addr,
+                app_admin,
+                routes,
+            } => {
+                let admin = deps.api.addr_validate(&app_admin)?;
+                _register(deps.storage, &env, &admin, &app_addr, routes)
+            }
+        },
+        SudoMsg::SdjwtVerifier(msg) => match msg {
+            SdjwtVerifierSudoMsg::VerifyWithRevocationProof {
+                app_addr,
+                route_id,
+                presentation,
+                additional_requirements,
+                revocation_proof,
+            } => handle_sudo_verify(
+                deps,
+                env,
+                app_addr,
+                route_id,
+                presentation,
+                additional_requirements,
+                Some(revocation_proof),
+            ),
+            SdjwtVerifierSudoMsg::SetTrustedIssuer {
+                name,
+                issuer_data,
+                metadata,
+            } => _set_trusted_issuer(deps.storage, name, issuer_data, metadata),
+            SdjwtVerifierSudoMsg::RemoveTrustedIssuer { name } => {
+                _remove_trusted_issuer(deps.storage, name)
+            }
+            SdjwtVerifierSudoMsg::SuspendIssuer {
+                iss,
+                key_thumbprint,
+                reason,
+            } => _suspend_issuer(deps.storage, &env, iss, key_thumbprint, reason),
+            SdjwtVerifierSudoMsg::ReinstateIssuer {
+                iss,
+                key_thumbprint,
+            } => _reinstate_issuer(deps.storage, iss, key_thumbprint),
+            SdjwtVerifierSudoMsg::SetKeyCompromised {
+                app_addr,
+                key_thumbprint,
+                cutoff,
+                allowed_idx,
+            } => _set_key_compromised(
+                deps.storage,
+                app_addr.as_deref(),
+                key_thumbprint,
+                KeyCompromise {
+                    cutoff,
+                    allowed_idx,
+                },
+            ),
+            SdjwtVerifierSudoMsg::ClearKeyCompromised {
+                app_addr,
+                key_thumbprint,
+            } => _clear_key_compromised(deps.storage, app_addr.as_deref(), key_thumbprint),
+            SdjwtVerifierSudoMsg::UpdateStatusList {
+                app_addr,
+                route_id,
+                status_list,
+            } => _update_route_status_list(deps.storage, &env, app_addr, route_id, status_list),
+            SdjwtVerifierSudoMsg::UpdateRevocationRoot {
+                app_addr,
+                route_id,
+                root,
+            } => _update_route_revocation_root(deps.storage, &env, app_addr, route_id, root),
+        },
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, SdjwtVerifierError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(SdjwtVerifierError::InvalidMigration(stored.contract));
+    }
+
+    // Earlier versions were instantiated without an owner, which is then set here.
+    // An existing owner is only changed with `UpdateOwner`
+    let owner = match OWNER.may_load(deps.storage)? {
+        Some(owner) => owner,
+        None => {
+            let owner = msg
+                .owner
+                .ok_or(SdjwtVerifierError::MigrationOwnerRequired)?;
+            let owner = deps.api.addr_validate(&owner)?;
+            OWNER.save(deps.storage, &owner)?;
+            owner
+        }
+    };
+
+    // The storage layout is migrated once, from the versions before `STORAGE_MIGRATION_VERSION`
+    let from_version = ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let (migrated_routes, migrated_requests) =
+        if (from_version.major, from_version.minor) < STORAGE_MIGRATION_VERSION {
+            // Moves the revocation lists of existing routes out of their requirements
+            // and in flight requests now have a list of subscribed routes
+            (
+                migrate_routes(deps.storage)?,
+                migrate_pending_requests(deps.storage)?,
+            )
+        } else {
+            (0, 0)
+        };
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("owner", owner)
+        .add_attribute("migrated_routes", migrated_routes.to_string())
+        .add_attribute("migrated_pending_requests", migrated_requests.to_string()))
+}
+
 // IBC entry points
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_channel_open(
@@ -150,19 +508,19 @@ pub fn ibc_channel_open(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_channel_connect(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     msg: IbcChannelConnectMsg,
 ) -> StdResult<IbcBasicResponse> {
-    Ok(ibc_channel_connect_handler(deps, msg)?)
+    Ok(ibc_channel_connect_handler(deps, env, msg)?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_channel_close(
-    _deps: DepsMut,
-    _env: Env,
-    _msg: IbcChannelCloseMsg,
+    deps: DepsMut,
+    env: Env,
+    msg: IbcChannelCloseMsg,
 ) -> StdResult<IbcBasicResponse> {
-    ibc_channel_close_handler()
+    Ok(ibc_channel_close_handler(deps, env, msg)?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -179,17 +537,17 @@ pub fn ibc_packet_receive(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_packet_ack(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     msg: IbcPacketAckMsg,
 ) -> StdResult<IbcBasicResponse> {
-    Ok(ibc_packet_ack_handler(deps, msg)?)
+    Ok(ibc_packet_ack_handler(deps, env, msg)?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn ibc_packet_timeout(
-    _deps: DepsMut,
-    _env: Env,
-    _msg: IbcPacketTimeoutMsg,
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
 ) -> StdResult<IbcBasicResponse> {
-    Ok(IbcBasicResponse::new().add_attribute("action", "ibc_packet_timeout"))
+    Ok(ibc_packet_timeout_handler(deps, env, msg)?)
 }
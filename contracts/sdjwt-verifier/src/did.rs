@@ -0,0 +1,175 @@
+use cosmwasm_std::{from_json, to_json_binary, Binary};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, OctetKeyPairParameters,
+    OctetKeyPairType,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{errors::SdjwtVerifierError, registry::to_base64url, verifier::parse_issuer_jwk};
+
+const DID_CHEQD_PREFIX: &str = "did:cheqd:";
+/// The multicodec prefix of Ed25519 public keys (`0xed` as an unsigned varint)
+const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The parts of a DID Document the issuer keys are taken from
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidDocument {
+    id: String,
+    #[serde(default)]
+    verification_method: Vec<VerificationMethod>,
+    #[serde(default)]
+    assertion_method: Vec<AssertionMethod>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationMethod {
+    id: String,
+    #[serde(rename = "type")]
+    method_type: String,
+    public_key_jwk: Option<Value>,
+    public_key_multibase: Option<String>,
+    public_key_base58: Option<String>,
+}
+
+/// An assertion method is a reference to a verification method or is embedded
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AssertionMethod {
+    Reference(String),
+    Embedded(VerificationMethod),
+}
+
+/// The DID and the assertion method keys of a cheqd DID Document,
+/// with their verification method id as `kid`. Methods without an Ed25519 key are skipped.
+/// The document must be the one of the DID the resource is linked to, `collection_id`
+pub fn did_document_keys(
+    data: &Binary,
+    collection_id: &str,
+) -> Result<(String, Vec<Jwk>), SdjwtVerifierError> {
+    let invalid = |reason: &str| SdjwtVerifierError::DidDocumentInvalid(reason.to_string());
+
+    let document: DidDocument = from_json(data).map_err(|e| invalid(&e.to_string()))?;
+    let did = document.id.as_str();
+    let unique_id = did
+        .strip_prefix(DID_CHEQD_PREFIX)
+        .map(|id| id.rsplit(':').next().unwrap_or(id));
+    if unique_id != Some(collection_id) {
+        return Err(invalid(&format!("{did} is not the DID of the resource")));
+    }
+
+    let mut pubkeys = vec![];
+    for method in &document.assertion_method {
+        let method = match method {
+            AssertionMethod::Embedded(method) => method,
+            AssertionMethod::Reference(id) => {
+                let id = absolute_key_id(did, id);
+                document
+                    .verification_method
+                    .iter()
+                    .find(|method| absolute_key_id(did, &method.id) == id)
+                    .ok_or_else(|| invalid(&format!("verification method {id} not found")))?
+            }
+        };
+        if let Some(mut pubkey) = verification_method_key(method).map_err(|e| invalid(&e))? {
+            pubkey.common.key_id = Some(absolute_key_id(did, &method.id));
+            pubkeys.push(pubkey);
+        }
+    }
+
+    if pubkeys.is_empty() {
+        return Err(invalid("no supported assertion method"));
+    }
+    Ok((document.id, pubkeys))
+}
+
+/// The absolute id of a verification method, which can be relative to the DID, e.g. `#key-1`
+fn absolute_key_id(did: &str, id: &str) -> String {
+    if id.starts_with('#') {
+        format!("{did}{id}")
+    } else {
+        id.to_string()
+    }
+}
+
+/// The key of a verification method, `None` if it is not an Ed25519 key
+fn verification_method_key(method: &VerificationMethod) -> Result<Option<Jwk>, String> {
+    match method.method_type.as_str() {
+        "JsonWebKey2020" => {
+            let jwk = method
+                .public_key_jwk
+                .as_ref()
+                .ok_or("publicKeyJwk missing")?;
+            match to_json_binary(jwk)
+                .map_err(SdjwtVerifierError::from)
+                .and_then(|jwk| parse_issuer_jwk(&jwk))
+            {
+                Ok(jwk) => Ok(Some(jwk)),
+                Err(SdjwtVerifierError::UnsupportedKeyType) => Ok(None),
+                Err(e) => Err(format!("{}: {e}", method.id)),
+            }
+        }
+        "Ed25519VerificationKey2020" => {
+            let multibase = method
+                .public_key_multibase
+                .as_deref()
+                .ok_or("publicKeyMultibase missing")?;
+            // Base58btc encoded, with the Ed25519 multicodec prefix
+            let key = multibase
+                .strip_prefix('z')
+                .and_then(base58_decode)
+                .and_then(|key| key.strip_prefix(&ED25519_MULTICODEC).map(<[u8]>::to_vec))
+                .ok_or_else(|| format!("{}: invalid publicKeyMultibase", method.id))?;
+            ed25519_jwk(&key)
+                .map(Some)
+                .ok_or_else(|| format!("{}: invalid Ed25519 key", method.id))
+        }
+        "Ed25519VerificationKey2018" => {
+            let key = method
+                .public_key_base58
+                .as_deref()
+                .and_then(base58_decode)
+                .ok_or_else(|| format!("{}: invalid publicKeyBase58", method.id))?;
+            ed25519_jwk(&key)
+                .map(Some)
+                .ok_or_else(|| format!("{}: invalid Ed25519 key", method.id))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn ed25519_jwk(key: &[u8]) -> Option<Jwk> {
+    (key.len() == 32).then(|| Jwk {
+        common: CommonParameters::default(),
+        algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: to_base64url(key),
+        }),
+    })
+}
+
+fn base58_decode(data: &str) -> Option<Vec<u8>> {
+    // Little endian base 256 digits of the decoded number
+    let mut bytes: Vec<u8> = vec![];
+    for c in data.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    // Leading ones encode leading zero bytes
+    let zeros = data.bytes().take_while(|&c| c == b'1').count();
+    bytes.extend(std::iter::repeat_n(0, zeros));
+    bytes.reverse();
+    Some(bytes)
+}
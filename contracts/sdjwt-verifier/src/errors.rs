@@ -1,6 +1,6 @@
-use avida_cheqd::ibc::ChannelError;
+use avida_cheqd::ibc::{ChannelError, ResourceError};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp};
 use sd_jwt_rs::error::Error as SdJwtRsError;
 use serde_json::error::Error as SerdeJsonError;
 use thiserror::Error;
@@ -44,6 +44,46 @@ pub enum SdjwtVerifierResultError {
     VerifiedClaimsToBinaryError(String),
     #[error("SdJwtRsError: {0}")]
     SdJwtRsError(String),
+    #[error("x5c chain invalid: {0}")]
+    X5cChainInvalid(String),
+    #[error("x5c certificate not valid at block time: {0}")]
+    X5cCertificateNotValid(String),
+    #[error("x5c certificate key usage not permitted: {0}")]
+    X5cKeyUsage(String),
+    #[error("x5c chain is not anchored to a trusted root")]
+    X5cUntrustedRoot,
+    #[error("Std error: {0}")]
+    Std(String),
+    #[error("Issuer suspended: {0}")]
+    IssuerSuspended(String),
+    #[error("Issuer keys too old: {0}")]
+    IssuerKeysNotFresh(String),
+    #[error("Issuer key compromised: {0}")]
+    KeyCompromised(String),
+    #[error("Status claim invalid: {0}")]
+    StatusClaimInvalid(String),
+    #[error("Status list not found: {0}")]
+    StatusListNotFound(String),
+    #[error("Status list expired: {0}")]
+    StatusListExpired(String),
+    #[error("Status list index out of range: {0}")]
+    StatusIdxOutOfRange(u64),
+    #[error("Credential status invalid: {0}")]
+    StatusInvalid(u64),
+    #[error("Credential status suspended: {0}")]
+    StatusSuspended(u64),
+    #[error("Credential status not accepted: {0} - {1}")]
+    StatusNotAccepted(u64, u8),
+    #[error("Revocation root not committed for the route")]
+    RevocationRootNotCommitted,
+    #[error("Revocation proof not provided")]
+    RevocationProofNotProvided,
+    #[error("Revocation proof invalid: {0}")]
+    RevocationProofInvalid(String),
+    #[error("Revocation proof does not match a current revocation root: {0}")]
+    RevocationProofRootMismatch(u64),
+    #[error("Shared revocation list not found: {0}")]
+    SharedRevocationListNotFound(String),
 }
 
 impl From<SdJwtRsError> for SdjwtVerifierResultError {
@@ -52,6 +92,12 @@ impl From<SdJwtRsError> for SdjwtVerifierResultError {
     }
 }
 
+impl From<StdError> for SdjwtVerifierResultError {
+    fn from(err: StdError) -> SdjwtVerifierResultError {
+        SdjwtVerifierResultError::Std(err.to_string())
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum SdjwtVerifierError {
     #[error("Verifier Result Error {0}")]
@@ -62,14 +108,20 @@ pub enum SdjwtVerifierError {
     SudoValidationFailed,
     #[error("IBC returned resource format unexpected {0}")]
     ReturnedResourceFormat(String),
-    #[error("IBC channel already exists")]
-    ChannelAlreadyExists,
+    #[error("IBC channel {0} not found")]
+    ChannelNotFound(String),
+    #[error("IBC channel {0} is closed")]
+    ChannelClosed(String),
+    #[error("IBC channel {0} is already active")]
+    ChannelAlreadyActive(String),
     #[error("Serde JSON Error {0}")]
     SerdeJsonError(#[from] SerdeJsonError),
     #[error("{0}")]
     Std(#[from] StdError),
     #[error("channel error")]
     ChannelError(#[from] ChannelError),
+    #[error("{0}")]
+    ResourceError(#[from] ResourceError),
     #[error("data deserialization error")]
     DataDeserialization,
     #[error("App Already Registered")]
@@ -88,4 +140,50 @@ pub enum SdjwtVerifierError {
     IDXNotInRequirement,
     #[error("Revocation List type")]
     RevocationListType,
+    #[error("Invalid root certificate: {0}")]
+    InvalidRootCertificate(String),
+    #[error("Trusted issuer not found: {0}")]
+    TrustedIssuerNotFound(String),
+    #[error("Either iss or key thumbprint must be provided")]
+    SuspensionTargetNotProvided,
+    #[error("Issuer not suspended: {0}")]
+    IssuerNotSuspended(String),
+    #[error("Key not marked as compromised: {0}")]
+    KeyNotCompromised(String),
+    #[error("Status list token invalid: {0}")]
+    StatusListTokenInvalid(String),
+    #[error("Status list is older than the stored one")]
+    StatusListOutdated,
+    #[error("Cannot migrate from contract: {0}")]
+    InvalidMigration(String),
+    #[error("An owner is required to migrate a contract without one")]
+    MigrationOwnerRequired,
+    #[error("Revocation root must be 32 bytes")]
+    RevocationRootInvalid,
+    #[error("Revocation range invalid: {0} - {1}")]
+    RevocationRangeInvalid(u64, u64),
+    #[error("Revocation update larger than {0} idx")]
+    RevocationUpdateTooLarge(u64),
+    #[error("Revocation delegate issuer must not be empty")]
+    RevocationDelegateIssuerEmpty,
+    #[error("Shared revocation list not found: {0}")]
+    SharedRevocationListNotFound(String),
+    #[error("Shared revocation list already exists: {0}")]
+    SharedRevocationListAlreadyExists(String),
+    #[error("Route has no cheqd key sources")]
+    NoKeySources,
+    #[error("Issuer keys can be refreshed after {0}")]
+    KeyRefreshTooSoon(Timestamp),
+    #[error("Resource version {1} does not match the pinned version {0}")]
+    ResourceVersionMismatch(String, String),
+    #[error("Resource checksum does not match the pinned checksum {0}")]
+    ResourceChecksumMismatch(String),
+    #[error("Resource created at {0} is too old")]
+    ResourceNotFresh(String),
+    #[error("Resource maximum age must be at most {0} days")]
+    ResourceMaxAgeTooLarge(u64),
+    #[error("The verifier fetches resources from cheqd {0}, not {1}")]
+    CheqdNetworkMismatch(String, String),
+    #[error("DID Document invalid: {0}")]
+    DidDocumentInvalid(String),
 }
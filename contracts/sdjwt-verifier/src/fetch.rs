@@ -0,0 +1,683 @@
+use avida_cheqd::types::{LinkedResourceMetadata, ResourceReqPacket};
+use avida_common::types::RouteId;
+use cosmwasm_std::{
+    ensure, DepsMut, Empty, Env, Event, Order, Response, StdResult, Storage, SubMsg, Timestamp,
+};
+use cw_storage_plus::Bound;
+use jsonwebtoken::jwk::Jwk;
+
+use crate::{
+    channel::active_channel,
+    errors::SdjwtVerifierError,
+    state::{
+        APP_ROUTES_REQUIREMENTS, DEFAULT_PAGE_LIMIT, FETCH_FAILURES, FETCH_RETRY_QUEUE,
+        LEGACY_PENDING_REQUESTS, MAX_PAGE_LIMIT, PENDING_VERIFICATION_REQ_REQUESTS,
+        QUEUED_RESOURCE_REQUESTS, ROUTE_KEY_SOURCES, ROUTE_KEY_VERSIONS, ROUTE_SOURCE_KEYS,
+    },
+    types::{
+        FetchFailure, IssuerKeys, PendingResource, PendingRoute, ResolvedKeyVersion,
+        ResourceFreshness, ResourceVersion, RouteKeySource, RouteStatus, RouteStatusResponse,
+    },
+    verifier::{
+        extend_response, resource_packet, send_resource_request, subscribe_resource_request,
+    },
+};
+
+/// Number of attempts (the initial request included) before a failed fetch is no longer retried
+pub const MAX_FETCH_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled after each failed attempt
+pub const FETCH_RETRY_BASE_DELAY_SECS: u64 = 60;
+/// Minimum time between two fetches of the issuer keys of a route
+pub const MIN_KEY_REFRESH_INTERVAL_SECS: u64 = 3600;
+/// The source of the issuer keys directly provided on registration
+pub const INLINE_KEYS_SOURCE: &str = "inline";
+const DEFAULT_RETRY_LIMIT: u32 = 5;
+/// Maximum number of fetches sent by a `RetryPendingFetches` call or on channel activation
+pub const MAX_RETRY_LIMIT: u32 = 10;
+
+/// Records a failed resource fetch (timeout, error ack or unusable resource) on the route,
+/// if it is still registered.
+/// The fetch is scheduled for a retry with exponential backoff until `MAX_FETCH_ATTEMPTS`
+pub fn record_fetch_failure(
+    storage: &mut dyn Storage,
+    env: &Env,
+    resource_req: ResourceReqPacket,
+    pending_route: PendingRoute,
+    reason: String,
+) -> StdResult<Event> {
+    let resource_key = resource_req.to_string();
+
+    let PendingRoute {
+        app_addr,
+        route_id,
+        resource,
+        source,
+    } = pending_route;
+
+    let mut event = Event::new("resource_fetch_failed")
+        .add_attribute("app_addr", &app_addr)
+        .add_attribute("route_id", route_id.to_string())
+        .add_attribute("resource", &resource_key)
+        .add_attribute("reason", &reason);
+
+    if !APP_ROUTES_REQUIREMENTS.has(storage, (app_addr.clone(), route_id)) {
+        return Ok(event);
+    }
+
+    let attempts = match FETCH_FAILURES.may_load(storage, (&app_addr, route_id, &resource_key))? {
+        Some(previous) => {
+            unschedule_retry(storage, &app_addr, route_id, &resource_key, &previous);
+            previous.attempts + 1
+        }
+        None => 1,
+    };
+
+    let next_retry =
+        (attempts < MAX_FETCH_ATTEMPTS).then(|| env.block.time.plus_seconds(retry_delay(attempts)));
+    if let Some(next_retry) = next_retry {
+        FETCH_RETRY_QUEUE.save(
+            storage,
+            (next_retry.seconds(), (&app_addr, route_id), &resource_key),
+            &Empty {},
+        )?;
+        event = event.add_attribute("next_retry", next_retry.seconds().to_string());
+    }
+
+    FETCH_FAILURES.save(
+        storage,
+        (&app_addr, route_id, &resource_key),
+        &FetchFailure {
+            resource_req,
+            resource,
+            source,
+            reason,
+            failed_at: env.block.time,
+            attempts,
+            next_retry,
+        },
+    )?;
+    Ok(event.add_attribute("attempts", attempts.to_string()))
+}
+
+/// The backoff after the given number of failed attempts, below `MAX_FETCH_ATTEMPTS`
+fn retry_delay(attempts: u32) -> u64 {
+    FETCH_RETRY_BASE_DELAY_SECS << (attempts - 1)
+}
+
+fn unschedule_retry(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    resource_key: &str,
+    failure: &FetchFailure,
+) {
+    if let Some(next_retry) = failure.next_retry {
+        FETCH_RETRY_QUEUE.remove(
+            storage,
+            (next_retry.seconds(), (app_addr, route_id), resource_key),
+        );
+    }
+}
+
+/// Sends the requests queued until the channel was connected, then re-sends the failed fetches
+/// that are due for a retry, oldest first.
+/// Anyone can call this, at most `MAX_RETRY_LIMIT` fetches are sent per call
+pub fn handle_retry_pending_fetches(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, SdjwtVerifierError> {
+    let limit = limit.unwrap_or(DEFAULT_RETRY_LIMIT).min(MAX_RETRY_LIMIT) as usize;
+    let now = env.block.time.seconds();
+
+    let queued = dispatch_queued_requests(deps.storage, &env, limit)?;
+    let limit = limit - queued.len();
+
+    let due = FETCH_RETRY_QUEUE
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take_while(|key| key.as_ref().map_or(true, |(time, ..)| *time <= now))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "retry_pending_fetches")
+        .add_attribute("dispatched", queued.len().to_string())
+        .add_attribute("retried", due.len().to_string())
+        .add_submessages(queued);
+
+    for (time, (app_addr, route_id), resource_key) in due {
+        FETCH_RETRY_QUEUE.remove(deps.storage, (time, (&app_addr, route_id), &resource_key));
+
+        let mut failure =
+            FETCH_FAILURES.load(deps.storage, (&app_addr, route_id, &resource_key))?;
+        failure.next_retry = None;
+        FETCH_FAILURES.save(deps.storage, (&app_addr, route_id, &resource_key), &failure)?;
+
+        let retry = send_resource_request(
+            deps.storage,
+            &env,
+            failure.resource_req,
+            PendingRoute {
+                app_addr,
+                route_id,
+                resource: failure.resource,
+                source: failure.source,
+            },
+        )?;
+        res = extend_response(res, retry);
+    }
+
+    Ok(res)
+}
+
+/// Sends at most `limit` of the resource requests queued until a channel was active,
+/// none while there is no active channel
+pub fn dispatch_queued_requests(
+    storage: &mut dyn Storage,
+    env: &Env,
+    limit: usize,
+) -> StdResult<Vec<SubMsg>> {
+    let Some(channel_id) = active_channel(storage)? else {
+        return Ok(vec![]);
+    };
+    let queued = QUEUED_RESOURCE_REQUESTS
+        .range(storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut msgs = Vec::with_capacity(queued.len());
+    for (resource_key, resource_req) in queued {
+        QUEUED_RESOURCE_REQUESTS.remove(storage, &resource_key);
+        msgs.push(resource_packet(channel_id.clone(), env, &resource_req)?);
+    }
+    Ok(msgs)
+}
+
+/// Clears the recorded failure and retry of a route resource, once it is requested again or fetched
+pub fn clear_fetch_failure(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    resource_req: &ResourceReqPacket,
+) -> StdResult<()> {
+    let resource_key = resource_req.to_string();
+    if let Some(failure) = FETCH_FAILURES.may_load(storage, (app_addr, route_id, &resource_key))? {
+        unschedule_retry(storage, app_addr, route_id, &resource_key, &failure);
+        FETCH_FAILURES.remove(storage, (app_addr, route_id, &resource_key));
+    }
+    Ok(())
+}
+
+pub fn query_fetch_failures(
+    storage: &dyn Storage,
+    app_addr: String,
+    route_id: RouteId,
+) -> StdResult<Vec<FetchFailure>> {
+    FETCH_FAILURES
+        .prefix((&app_addr, route_id))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, failure)| failure))
+        .collect()
+}
+
+/// Removes all the recorded fetch failures and retries of the route
+pub fn clear_fetch_failures(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+) -> StdResult<()> {
+    let failures = FETCH_FAILURES
+        .prefix((app_addr, route_id))
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (resource_key, failure) in failures {
+        unschedule_retry(storage, app_addr, route_id, &resource_key, &failure);
+        FETCH_FAILURES.remove(storage, (app_addr, route_id, &resource_key));
+    }
+    Ok(())
+}
+
+/// Re-sends the requests for all the key sources of the route (anyone), so that key rotations
+/// published on cheqd are picked up. The current keys are used until the new ones are fetched
+pub fn handle_refresh_issuer_keys(
+    deps: DepsMut,
+    env: Env,
+    app_addr: String,
+    route_id: RouteId,
+) -> Result<Response, SdjwtVerifierError> {
+    if !APP_ROUTES_REQUIREMENTS.has(deps.storage, (app_addr.clone(), route_id)) {
+        return Err(SdjwtVerifierError::RouteNotRegistered);
+    }
+
+    let sources = ROUTE_KEY_SOURCES
+        .prefix((&app_addr, route_id))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, source)| source))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let last_requested = sources
+        .iter()
+        .map(|source| source.requested_at)
+        .max()
+        .ok_or(SdjwtVerifierError::NoKeySources)?;
+    let next_refresh = last_requested.plus_seconds(MIN_KEY_REFRESH_INTERVAL_SECS);
+    ensure!(
+        env.block.time >= next_refresh,
+        SdjwtVerifierError::KeyRefreshTooSoon(next_refresh)
+    );
+
+    let mut res = Response::new()
+        .add_attribute("action", "refresh_issuer_keys")
+        .add_attribute("app_addr", &app_addr)
+        .add_attribute("route_id", route_id.to_string());
+
+    for source in sources {
+        let resource_req = source.resource_req.clone();
+        save_key_source(
+            deps.storage,
+            &app_addr,
+            route_id,
+            RouteKeySource {
+                requested_at: env.block.time,
+                ..source
+            },
+        )?;
+        // The resource is fetched again, even if it is cached
+        clear_fetch_failure(deps.storage, &app_addr, route_id, &resource_req)?;
+        let request = subscribe_resource_request(
+            deps.storage,
+            &env,
+            resource_req,
+            PendingRoute {
+                app_addr: app_addr.clone(),
+                route_id,
+                resource: PendingResource::IssuerKeys,
+                source: None,
+            },
+        )?;
+        res = extend_response(res, request);
+    }
+
+    Ok(res)
+}
+
+/// Records a cheqd resource the route issuer keys are requested from
+pub fn save_key_source(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    source: RouteKeySource,
+) -> StdResult<()> {
+    let resource_key = source.resource_req.to_string();
+    let fetched_at = ROUTE_KEY_SOURCES
+        .may_load(storage, (app_addr, route_id, &resource_key))?
+        .and_then(|source| source.fetched_at);
+    ROUTE_KEY_SOURCES.save(
+        storage,
+        (app_addr, route_id, &resource_key),
+        &RouteKeySource {
+            fetched_at,
+            ..source
+        },
+    )
+}
+
+/// The route key source resolved by a resource request, `None` for other resources
+pub fn pending_key_source(
+    storage: &dyn Storage,
+    resource_req: &ResourceReqPacket,
+    pending_route: &PendingRoute,
+) -> StdResult<Option<RouteKeySource>> {
+    if pending_route.resource != PendingResource::IssuerKeys {
+        return Ok(None);
+    }
+    let source = pending_route.source.as_ref().unwrap_or(resource_req);
+    ROUTE_KEY_SOURCES.may_load(
+        storage,
+        (
+            &pending_route.app_addr,
+            pending_route.route_id,
+            &source.to_string(),
+        ),
+    )
+}
+
+/// Marks a key source of the route as fetched, if the route still has it
+pub fn set_key_source_fetched(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: &str,
+    route_id: RouteId,
+    resource_req: &ResourceReqPacket,
+) -> StdResult<()> {
+    let resource_key = resource_req.to_string();
+    if let Some(mut source) =
+        ROUTE_KEY_SOURCES.may_load(storage, (app_addr, route_id, &resource_key))?
+    {
+        source.fetched_at = Some(env.block.time);
+        ROUTE_KEY_SOURCES.save(storage, (app_addr, route_id, &resource_key), &source)?;
+    }
+    Ok(())
+}
+
+/// Removes all the key sources of the route, with their keys
+pub fn clear_key_sources(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+) -> StdResult<()> {
+    let resources = ROUTE_KEY_SOURCES
+        .prefix((app_addr, route_id))
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for resource_key in resources {
+        ROUTE_KEY_SOURCES.remove(storage, (app_addr, route_id, &resource_key));
+    }
+    let sources = ROUTE_SOURCE_KEYS
+        .prefix((app_addr, route_id))
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for source in sources {
+        ROUTE_SOURCE_KEYS.remove(storage, (app_addr, route_id, &source));
+    }
+    Ok(())
+}
+
+/// Replaces the keys of a route source and returns the merged keys of all the route sources.
+/// The keys directly provided take precedence, then the sources in the order of their resource
+pub fn merge_source_keys(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    source: &str,
+    keys: IssuerKeys,
+) -> StdResult<IssuerKeys> {
+    ROUTE_SOURCE_KEYS.save(storage, (app_addr, route_id, source), &keys)?;
+
+    let mut merged = ROUTE_SOURCE_KEYS
+        .may_load(storage, (app_addr, route_id, INLINE_KEYS_SOURCE))?
+        .unwrap_or_default();
+    for item in
+        ROUTE_SOURCE_KEYS
+            .prefix((app_addr, route_id))
+            .range(storage, None, None, Order::Ascending)
+    {
+        let (source, keys) = item?;
+        if source == INLINE_KEYS_SOURCE {
+            continue;
+        }
+        for (iss, key) in keys.issuers {
+            merged.issuers.entry(iss).or_insert(key);
+        }
+        for (key_id, key) in keys.key_ids {
+            merged.key_ids.entry(key_id).or_insert(key);
+        }
+    }
+    Ok(merged)
+}
+
+/// The ids of the route issuer keys by source
+pub fn query_route_key_ids(
+    storage: &dyn Storage,
+    app_addr: String,
+    route_id: RouteId,
+) -> StdResult<Vec<(String, Vec<String>)>> {
+    ROUTE_SOURCE_KEYS
+        .prefix((&app_addr, route_id))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(source, keys)| {
+                let mut key_ids: Vec<String> = keys
+                    .issuers
+                    .into_keys()
+                    .chain(keys.key_ids.into_keys())
+                    .collect();
+                key_ids.sort();
+                (source, key_ids)
+            })
+        })
+        .collect()
+}
+
+/// Checks a fetched key resource against the accepted versions of its source.
+/// Returns the newer version to request instead, when following the latest version
+pub fn check_key_version(
+    version: &ResourceVersion,
+    metadata: &LinkedResourceMetadata,
+) -> Result<Option<ResourceReqPacket>, SdjwtVerifierError> {
+    match version {
+        ResourceVersion::Requested => {}
+        ResourceVersion::Version(pinned) => ensure!(
+            &metadata.resource_version == pinned,
+            SdjwtVerifierError::ResourceVersionMismatch(
+                pinned.clone(),
+                metadata.resource_version.clone()
+            )
+        ),
+        ResourceVersion::Checksum(pinned) => ensure!(
+            metadata
+                .checksum
+                .as_ref()
+                .is_some_and(|checksum| checksum.eq_ignore_ascii_case(pinned)),
+            SdjwtVerifierError::ResourceChecksumMismatch(pinned.clone())
+        ),
+        ResourceVersion::Latest => {
+            if let Some(next_version_id) = metadata
+                .next_version_id
+                .as_ref()
+                .filter(|next_version_id| !next_version_id.is_empty())
+            {
+                return Ok(Some(ResourceReqPacket {
+                    resource_id: next_version_id.clone(),
+                    collection_id: metadata.resource_collection_id.clone(),
+                }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Checks a fetched key resource was created recently enough for its source
+pub fn check_key_freshness(
+    freshness: &ResourceFreshness,
+    metadata: &LinkedResourceMetadata,
+    now: Timestamp,
+) -> Result<(), SdjwtVerifierError> {
+    let created = metadata.created_at()?;
+    ensure!(
+        freshness.is_fresh(created, now),
+        SdjwtVerifierError::ResourceNotFresh(metadata.created.clone())
+    );
+    Ok(())
+}
+
+/// Records the cheqd resource version the keys of a route source were taken from
+pub fn save_key_version(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: &str,
+    route_id: RouteId,
+    source: &ResourceReqPacket,
+    metadata: LinkedResourceMetadata,
+) -> StdResult<()> {
+    let mut versions = ROUTE_KEY_VERSIONS
+        .may_load(storage, (app_addr, route_id))?
+        .unwrap_or_default();
+    versions.retain(|version| &version.source != source);
+    versions.push(ResolvedKeyVersion {
+        source: source.clone(),
+        metadata,
+    });
+    ROUTE_KEY_VERSIONS.save(storage, (app_addr, route_id), &versions, env.block.height)
+}
+
+/// Removes the key versions of the route, they are still available at earlier heights
+pub fn clear_key_versions(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: &str,
+    route_id: RouteId,
+) -> StdResult<()> {
+    if ROUTE_KEY_VERSIONS
+        .may_load(storage, (app_addr, route_id))?
+        .is_some()
+    {
+        ROUTE_KEY_VERSIONS.remove(storage, (app_addr, route_id), env.block.height)?;
+    }
+    Ok(())
+}
+
+/// The cheqd resource versions the route keys were taken from,
+/// at the start of the block `height` if given
+pub fn query_route_key_versions(
+    storage: &dyn Storage,
+    app_addr: String,
+    route_id: RouteId,
+    height: Option<u64>,
+) -> StdResult<Vec<ResolvedKeyVersion>> {
+    let versions = match height {
+        Some(height) => {
+            ROUTE_KEY_VERSIONS.may_load_at_height(storage, (&app_addr, route_id), height)?
+        }
+        None => ROUTE_KEY_VERSIONS.may_load(storage, (&app_addr, route_id))?,
+    };
+    Ok(versions.unwrap_or_default())
+}
+
+/// The stale route key source an issuer key was fetched from, if any
+pub fn stale_key_source(
+    storage: &dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    pubkey: &Jwk,
+    now: Timestamp,
+) -> StdResult<Option<ResourceReqPacket>> {
+    for source in stale_key_sources(storage, app_addr, route_id, now)? {
+        let keys = ROUTE_SOURCE_KEYS
+            .may_load(storage, (app_addr, route_id, &source.to_string()))?
+            .unwrap_or_default();
+        if keys
+            .issuers
+            .values()
+            .chain(keys.key_ids.values())
+            .any(|key| key == pubkey)
+        {
+            return Ok(Some(source));
+        }
+    }
+    Ok(None)
+}
+
+/// The route key sources whose resolved resource version is no longer fresh enough at `now`
+pub fn stale_key_sources(
+    storage: &dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    now: Timestamp,
+) -> StdResult<Vec<ResourceReqPacket>> {
+    let mut stale = vec![];
+    for version in ROUTE_KEY_VERSIONS
+        .may_load(storage, (app_addr, route_id))?
+        .unwrap_or_default()
+    {
+        let Some(source) = ROUTE_KEY_SOURCES
+            .may_load(storage, (app_addr, route_id, &version.source.to_string()))?
+        else {
+            continue;
+        };
+        // The created time was checked when the resource was fetched
+        let fresh = version
+            .metadata
+            .created_at()
+            .is_ok_and(|created| source.freshness.is_fresh(created, now));
+        if !fresh {
+            stale.push(version.source);
+        }
+    }
+    Ok(stale)
+}
+
+/// Whether the issuer keys of the route can be used, with its key sources and fetch failures.
+/// Routes without cheqd key sources are always ready
+pub fn query_route_status(
+    storage: &dyn Storage,
+    env: &Env,
+    app_addr: String,
+    route_id: RouteId,
+) -> Result<RouteStatusResponse, SdjwtVerifierError> {
+    if !APP_ROUTES_REQUIREMENTS.has(storage, (app_addr.clone(), route_id)) {
+        return Err(SdjwtVerifierError::RouteNotRegistered);
+    }
+
+    let sources = ROUTE_KEY_SOURCES
+        .prefix((&app_addr, route_id))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, source)| source))
+        .collect::<StdResult<Vec<_>>>()?;
+    let failures = query_fetch_failures(storage, app_addr.clone(), route_id)?;
+    let stale = stale_key_sources(storage, &app_addr, route_id, env.block.time)?;
+
+    // A failed fetch of a source is recorded for the requested resource,
+    // which is a newer version of the source resource when following the latest version
+    let has_failed = |source: &RouteKeySource| {
+        failures.iter().any(|failure| {
+            failure.source.as_ref().unwrap_or(&failure.resource_req) == &source.resource_req
+        })
+    };
+
+    let mut status = RouteStatus::Ready;
+    for source in &sources {
+        match source.fetched_at {
+            None if has_failed(source) => {
+                status = RouteStatus::FetchFailed;
+                break;
+            }
+            None => status = RouteStatus::PendingFetch,
+            Some(fetched_at) => {
+                if status == RouteStatus::Ready
+                    && (has_failed(source)
+                        || source.requested_at > fetched_at
+                        || stale.contains(&source.resource_req))
+                {
+                    status = RouteStatus::Stale;
+                }
+            }
+        }
+    }
+
+    Ok(RouteStatusResponse {
+        status,
+        sources,
+        failures,
+    })
+}
+
+/// The in flight resource requests with their subscribed routes, by `collection_id:resource_id`
+pub fn query_pending_fetches(
+    storage: &dyn Storage,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Vec<PendingRoute>)>> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    PENDING_VERIFICATION_REQ_REQUESTS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+/// Moves the in flight requests stored by earlier versions of the contract,
+/// which had a single route, to the subscribed routes of the requests.
+/// Returns the number of migrated requests
+pub fn migrate_pending_requests(storage: &mut dyn Storage) -> StdResult<u64> {
+    let requests = LEGACY_PENDING_REQUESTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let migrated = requests.len() as u64;
+    for (resource_key, pending_route) in requests {
+        LEGACY_PENDING_REQUESTS.remove(storage, &resource_key);
+        PENDING_VERIFICATION_REQ_REQUESTS.save(storage, &resource_key, &vec![pending_route])?;
+    }
+    Ok(migrated)
+}
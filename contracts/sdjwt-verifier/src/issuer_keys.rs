@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::{from_json, to_json_binary, Binary};
+use jsonwebtoken::jwk::Jwk;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    did::did_document_keys,
+    errors::SdjwtVerifierError,
+    types::{IssuerKeys, KeyResourceFormat},
+    verifier::parse_issuer_jwk,
+};
+
+/// RFC 7517 JWK Set
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Value>,
+}
+
+/// SD-JWT VC issuer metadata, as served at `/.well-known/jwt-vc-issuer`
+#[derive(Deserialize)]
+struct JwtVcIssuerMetadata {
+    issuer: String,
+    jwks: Option<JwkSet>,
+    jwks_uri: Option<String>,
+}
+
+/// Parses the issuer keys of a cheqd resource in the format declared by its source
+pub fn resource_issuer_keys(
+    format: &KeyResourceFormat,
+    data: &Binary,
+    collection_id: &str,
+) -> Result<IssuerKeys, SdjwtVerifierError> {
+    let invalid = |e: &dyn ToString| SdjwtVerifierError::ReturnedResourceFormat(e.to_string());
+
+    match format {
+        KeyResourceFormat::Avida => Ok(IssuerKeys {
+            issuers: from_json(data).map_err(|e| invalid(&e))?,
+            key_ids: HashMap::new(),
+        }),
+        KeyResourceFormat::DidDocument => {
+            let (did, pubkeys) = did_document_keys(data, collection_id)?;
+            Ok(issuer_keys(&did, pubkeys))
+        }
+        KeyResourceFormat::Jwks { issuer } => {
+            let jwks: JwkSet = from_json(data).map_err(|e| invalid(&e))?;
+            jwks_issuer_keys(issuer, jwks)
+        }
+        KeyResourceFormat::Jwk { issuer } => {
+            let jwk = parse_issuer_jwk(data)?;
+            Ok(issuer_keys(issuer, vec![jwk]))
+        }
+        KeyResourceFormat::JwtVcIssuer => {
+            let metadata: JwtVcIssuerMetadata = from_json(data).map_err(|e| invalid(&e))?;
+            match (metadata.jwks, metadata.jwks_uri) {
+                (Some(jwks), _) => jwks_issuer_keys(&metadata.issuer, jwks),
+                // Remote key sets cannot be fetched by the contract
+                (None, Some(_)) => Err(invalid(&"jwks_uri is not supported, use inline jwks")),
+                (None, None) => Err(invalid(&"jwks missing")),
+            }
+        }
+    }
+}
+
+/// The key of `iss` selected by a `kid`, which is relative to the issuer (`#key-1`),
+/// absolute (`did:cheqd:testnet:<id>#key-1`) or a JWK Set `kid`
+pub fn issuer_key_id(iss: &str, kid: &str) -> String {
+    if kid.starts_with('#') {
+        format!("{iss}{kid}")
+    } else if kid
+        .strip_prefix(iss)
+        .is_some_and(|fragment| fragment.starts_with('#'))
+    {
+        kid.to_string()
+    } else {
+        format!("{iss}#{kid}")
+    }
+}
+
+/// The supported (Ed25519) keys of a JWK Set, other keys are ignored
+fn jwks_issuer_keys(issuer: &str, jwks: JwkSet) -> Result<IssuerKeys, SdjwtVerifierError> {
+    let mut pubkeys = vec![];
+    for key in jwks.keys {
+        match parse_issuer_jwk(&to_json_binary(&key)?) {
+            Ok(jwk) => pubkeys.push(jwk),
+            Err(SdjwtVerifierError::UnsupportedKeyType) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    if pubkeys.is_empty() {
+        return Err(SdjwtVerifierError::ReturnedResourceFormat(
+            "no supported key in the JWK Set".to_string(),
+        ));
+    }
+    Ok(issuer_keys(issuer, pubkeys))
+}
+
+/// The first key is the key of the issuer and the keys with a `kid` are also kept by their key id
+fn issuer_keys(issuer: &str, pubkeys: Vec<Jwk>) -> IssuerKeys {
+    let mut keys = IssuerKeys::default();
+    for pubkey in pubkeys {
+        if let Some(kid) = &pubkey.common.key_id {
+            keys.key_ids
+                .insert(issuer_key_id(issuer, kid), pubkey.clone());
+        }
+        keys.issuers.entry(issuer.to_string()).or_insert(pubkey);
+    }
+    keys
+}
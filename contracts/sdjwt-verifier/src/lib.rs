@@ -1,6 +1,14 @@
+mod channel;
 pub mod contract;
+mod did;
 pub mod errors;
+mod fetch;
+mod issuer_keys;
 pub mod msg;
+mod registry;
+mod revocation;
 mod state;
+mod status_list;
 pub mod types;
 mod verifier;
+mod x509;
@@ -1,5 +1,15 @@
-use avida_common::types::RouteId;
+use std::collections::BTreeMap;
+
+use avida_cheqd::did_url::CheqdNetwork;
+use avida_common::types::{
+    AvidaVerifierExecuteMsg, AvidaVerifierSudoMsg, IdxRange, IssuerSourceOrData, RouteId,
+    VerfiablePresentation,
+};
+use cosmwasm_schema::cw_schema::{DefinitionReference, Node, NodeType, SchemaVisitor, Schemaifier};
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_schema::serde::{Deserialize, Serialize};
 use cosmwasm_schema::QueryResponses;
+use cosmwasm_std::{Binary, Timestamp};
 
 use crate::types::InitRegistration;
 
@@ -8,6 +18,267 @@ use crate::types::InitRegistration;
 pub struct InstantiateMsg {
     pub max_presentation_len: usize,
     pub init_registrations: Vec<InitRegistration>,
+    /// The cheqd network the resources are fetched from, checked against registered DID resources
+    #[serde(default)]
+    pub cheqd_network: Option<CheqdNetwork>,
+}
+
+// Contract migration parameters
+#[cosmwasm_schema::cw_serde]
+pub struct MigrateMsg {
+    /// The contract owner, required when migrating from a version without one
+    #[serde(default)]
+    pub owner: Option<String>,
+}
+
+// Execute messages, the common Avida verifier messages keep their format
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "cosmwasm_schema::serde", untagged)]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub enum ExecuteMsg {
+    Avida(AvidaVerifierExecuteMsg),
+    SdjwtVerifier(SdjwtVerifierExecuteMsg),
+}
+
+impl Schemaifier for ExecuteMsg {
+    fn visit_schema(visitor: &mut SchemaVisitor) -> DefinitionReference {
+        untagged_enum_schema::<Self, AvidaVerifierExecuteMsg, SdjwtVerifierExecuteMsg>(visitor)
+    }
+}
+
+impl From<AvidaVerifierExecuteMsg> for ExecuteMsg {
+    fn from(msg: AvidaVerifierExecuteMsg) -> Self {
+        ExecuteMsg::Avida(msg)
+    }
+}
+
+impl From<SdjwtVerifierExecuteMsg> for ExecuteMsg {
+    fn from(msg: SdjwtVerifierExecuteMsg) -> Self {
+        ExecuteMsg::SdjwtVerifier(msg)
+    }
+}
+
+// Sdjwt-Verifier specific execute messages
+#[cosmwasm_schema::cw_serde]
+pub enum SdjwtVerifierExecuteMsg {
+    /// `Verify` with a proof that the credential is not revoked,
+    /// for routes that only commit to the root of their revocations
+    VerifyWithRevocationProof {
+        presentation: VerfiablePresentation,
+        route_id: RouteId,
+        app_addr: Option<String>,
+        additional_requirements: Option<Binary>,
+        revocation_proof: Binary,
+    },
+    /// Adds or replaces an entry in the contract-wide trusted issuer registry (owner only).
+    /// `issuer_data` has the same format as directly provided `IssuerSourceOrData.data_or_location`
+    SetTrustedIssuer {
+        name: String,
+        issuer_data: Binary,
+        metadata: Option<Binary>,
+    },
+    /// Removes an entry from the trusted issuer registry (owner only)
+    RemoveTrustedIssuer { name: String },
+    /// Transfers the contract ownership (owner only)
+    UpdateOwner { new_owner: String },
+    /// Suspends an issuer across all apps and routes (owner only),
+    /// by its `iss` and / or its key thumbprint (RFC 7638, base64url SHA-256)
+    SuspendIssuer {
+        iss: Option<String>,
+        key_thumbprint: Option<String>,
+        reason: Option<String>,
+    },
+    /// Lifts a suspension set by `SuspendIssuer` (owner only)
+    ReinstateIssuer {
+        iss: Option<String>,
+        key_thumbprint: Option<String>,
+    },
+    /// Marks an issuer key (RFC 7638 thumbprint) as compromised,
+    /// credentials signed with it are only accepted if their `iat` is before `cutoff`
+    /// and, if `allowed_idx` is set, their `idx` is listed.
+    /// Set by the app admin for the routes of `app_addr`, or contract-wide by the owner
+    SetKeyCompromised {
+        app_addr: Option<String>,
+        key_thumbprint: String,
+        cutoff: Timestamp,
+        allowed_idx: Option<Vec<u64>>,
+    },
+    /// Removes a compromised key marker set by `SetKeyCompromised`
+    ClearKeyCompromised {
+        app_addr: Option<String>,
+        key_thumbprint: String,
+    },
+    /// Stores a revocation status list, the Token Status List JWT, signed by an issuer trusted on the route.
+    /// If directly provided, anyone can submit it.
+    /// Fetching it from a trust registry can only be requested by the app admin
+    UpdateStatusList {
+        app_addr: String,
+        route_id: RouteId,
+        status_list: IssuerSourceOrData,
+    },
+    /// Commits the root of a sparse Merkle tree over the revoked credential `idx` of the route,
+    /// only the app admin can update it
+    UpdateRevocationRoot {
+        app_addr: String,
+        route_id: RouteId,
+        root: Binary,
+    },
+    /// Allows `delegate`, e.g. an issuer controlled address, to update the route revocation list
+    /// for the credentials of `iss` only (app admin only)
+    SetRevocationDelegate {
+        app_addr: String,
+        route_id: RouteId,
+        delegate: String,
+        iss: String,
+    },
+    /// Removes a revocation delegate set by `SetRevocationDelegate` (app admin only)
+    RemoveRevocationDelegate {
+        app_addr: String,
+        route_id: RouteId,
+        delegate: String,
+    },
+    /// Creates a named revocation list owned by the sender,
+    /// which the routes of any app can reference by `list_id` in their requirements.
+    /// `editors` can update the list besides the owner
+    CreateSharedRevocationList {
+        list_id: String,
+        editors: Vec<String>,
+    },
+    /// Transfers the ownership and updates the editors of a shared revocation list (list owner only)
+    UpdateSharedRevocationListAcl {
+        list_id: String,
+        new_owner: Option<String>,
+        add_editors: Vec<String>,
+        remove_editors: Vec<String>,
+    },
+    /// Updates a shared revocation list, taking effect on all the routes referencing it
+    /// (list owner or editors only). Revocations are applied before unrevocations
+    UpdateSharedRevocationList {
+        list_id: String,
+        revoke: Vec<u64>,
+        unrevoke: Vec<u64>,
+        revoke_ranges: Vec<IdxRange>,
+        unrevoke_ranges: Vec<IdxRange>,
+    },
+    /// Sends the resource fetches queued until the IBC channel was connected,
+    /// then re-sends the failed ones that are due for a retry (anyone).
+    /// At most `limit` fetches are sent per call
+    RetryPendingFetches { limit: Option<u32> },
+    /// Re-fetches the issuer keys of a route from its cheqd sources (anyone),
+    /// rate limited per route
+    RefreshIssuerKeys { app_addr: String, route_id: RouteId },
+    /// Switches the cheqd resource requests to another connected IBC channel (owner only).
+    /// The requests in flight on the previous channel are recorded as failed fetches,
+    /// to be retried on the new one
+    SetCheqdChannel { channel_id: String },
+}
+
+// Sudo messages (privileged operations), the common Avida verifier messages keep their format
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "cosmwasm_schema::serde", untagged)]
+#[schemars(crate = "cosmwasm_schema::schemars")]
+pub enum SudoMsg {
+    Avida(AvidaVerifierSudoMsg),
+    SdjwtVerifier(SdjwtVerifierSudoMsg),
+}
+
+impl Schemaifier for SudoMsg {
+    fn visit_schema(visitor: &mut SchemaVisitor) -> DefinitionReference {
+        untagged_enum_schema::<Self, AvidaVerifierSudoMsg, SdjwtVerifierSudoMsg>(visitor)
+    }
+}
+
+impl From<AvidaVerifierSudoMsg> for SudoMsg {
+    fn from(msg: AvidaVerifierSudoMsg) -> Self {
+        SudoMsg::Avida(msg)
+    }
+}
+
+impl From<SdjwtVerifierSudoMsg> for SudoMsg {
+    fn from(msg: SdjwtVerifierSudoMsg) -> Self {
+        SudoMsg::SdjwtVerifier(msg)
+    }
+}
+
+// Sdjwt-Verifier specific sudo messages, see `SdjwtVerifierExecuteMsg`
+#[cosmwasm_schema::cw_serde]
+pub enum SdjwtVerifierSudoMsg {
+    VerifyWithRevocationProof {
+        app_addr: String,
+        route_id: RouteId,
+        presentation: VerfiablePresentation,
+        additional_requirements: Option<Binary>,
+        revocation_proof: Binary,
+    },
+    SetTrustedIssuer {
+        name: String,
+        issuer_data: Binary,
+        metadata: Option<Binary>,
+    },
+    RemoveTrustedIssuer {
+        name: String,
+    },
+    SuspendIssuer {
+        iss: Option<String>,
+        key_thumbprint: Option<String>,
+        reason: Option<String>,
+    },
+    ReinstateIssuer {
+        iss: Option<String>,
+        key_thumbprint: Option<String>,
+    },
+    SetKeyCompromised {
+        app_addr: Option<String>,
+        key_thumbprint: String,
+        cutoff: Timestamp,
+        allowed_idx: Option<Vec<u64>>,
+    },
+    ClearKeyCompromised {
+        app_addr: Option<String>,
+        key_thumbprint: String,
+    },
+    UpdateStatusList {
+        app_addr: String,
+        route_id: RouteId,
+        status_list: IssuerSourceOrData,
+    },
+    UpdateRevocationRoot {
+        app_addr: String,
+        route_id: RouteId,
+        root: Binary,
+    },
+}
+
+/// The cw-schema derive does not support untagged enums,
+/// the schema of an untagged enum of the messages `A` and `B` is the enum of the cases of both
+fn untagged_enum_schema<T: Schemaifier, A: Schemaifier, B: Schemaifier>(
+    visitor: &mut SchemaVisitor,
+) -> DefinitionReference {
+    if let Some(reference) = visitor.get_reference::<T>() {
+        return reference;
+    }
+    A::visit_schema(visitor);
+    B::visit_schema(visitor);
+
+    let mut cases = BTreeMap::new();
+    let nodes = [visitor.get_schema::<A>(), visitor.get_schema::<B>()];
+    for node in nodes.into_iter().flatten() {
+        if let NodeType::Enum {
+            cases: node_cases, ..
+        } = &node.value
+        {
+            cases.extend(node_cases.clone());
+        }
+    }
+    let node = Node {
+        name: std::any::type_name::<T>().replace("::", "_").into(),
+        description: None,
+        value: NodeType::Enum {
+            discriminator: None,
+            cases,
+        },
+    };
+    visitor.insert(T::id(), node)
 }
 
 // Query messages
@@ -22,4 +293,110 @@ pub enum QueryMsg {
     GetRoutes { app_addr: String },
     #[returns(avida_common::types::RouteVerificationRequirements)]
     GetRouteRequirements { app_addr: String, route_id: RouteId },
+    #[returns(String)]
+    GetOwner {},
+    #[returns(crate::types::TrustedIssuer)]
+    GetTrustedIssuer { name: String },
+    #[returns(Vec<(String, crate::types::TrustedIssuer)>)]
+    GetTrustedIssuers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(Vec<(String, crate::types::IssuerSuspension)>)]
+    GetSuspendedIssuers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(Vec<(String, crate::types::IssuerSuspension)>)]
+    GetSuspendedKeys {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The `(app_addr, route_id)` of the routes with a key for the issuer `iss`
+    #[returns(Vec<(String, RouteId)>)]
+    RoutesByIssuer {
+        iss: String,
+        start_after: Option<(String, RouteId)>,
+        limit: Option<u32>,
+    },
+    /// The verified Token Status List of the issuer `iss` at `uri` stored for the route
+    #[returns(crate::types::StatusList)]
+    GetStatusList {
+        app_addr: String,
+        route_id: RouteId,
+        iss: String,
+        uri: String,
+    },
+    /// The compromised keys of `app_addr`, or the contract-wide ones if not set
+    #[returns(Vec<(String, crate::types::KeyCompromise)>)]
+    GetCompromisedKeys {
+        app_addr: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The committed revocation roots of the route, most recent first
+    #[returns(Vec<crate::types::RevocationRoot>)]
+    GetRevocationRoots { app_addr: String, route_id: RouteId },
+    /// Whether `idx` is revoked in the route revocation list.
+    /// With `iss`, whether a credential of `iss` is revoked, for all issuers or for `iss` only
+    #[returns(bool)]
+    IsRevoked {
+        app_addr: String,
+        route_id: RouteId,
+        idx: u64,
+        iss: Option<String>,
+    },
+    /// The revoked `idx` of the route revocation list in ascending order,
+    /// of the revocations scoped to `iss` or, if `None`, of the ones for all issuers
+    #[returns(Vec<u64>)]
+    RevocationList {
+        app_addr: String,
+        route_id: RouteId,
+        iss: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The addresses allowed to update the route revocations of an issuer
+    #[returns(Vec<crate::types::RevocationDelegate>)]
+    RevocationDelegates { app_addr: String, route_id: RouteId },
+    /// The owner and editors of a shared revocation list
+    #[returns(crate::types::SharedRevocationList)]
+    GetSharedRevocationList { list_id: String },
+    /// Whether `idx` is revoked in the shared revocation list
+    #[returns(bool)]
+    IsRevokedInSharedList { list_id: String, idx: u64 },
+    /// The revoked `idx` of the shared revocation list in ascending order
+    #[returns(Vec<u64>)]
+    SharedRevocationListEntries {
+        list_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The failed IBC resource fetches of the route, timed out or acknowledged with an error
+    #[returns(Vec<crate::types::FetchFailure>)]
+    GetFetchFailures { app_addr: String, route_id: RouteId },
+    /// Whether the issuer keys of the route are fetched and can be used
+    #[returns(crate::types::RouteStatusResponse)]
+    RouteStatus { app_addr: String, route_id: RouteId },
+    /// The in flight IBC resource requests with their subscribed routes, by `collection_id:resource_id`
+    #[returns(Vec<(String, Vec<crate::types::PendingRoute>)>)]
+    PendingFetches {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// The cheqd resource versions the route keys were taken from,
+    /// at the start of the block `height` if given
+    #[returns(Vec<crate::types::ResolvedKeyVersion>)]
+    RouteKeyVersions {
+        app_addr: String,
+        route_id: RouteId,
+        height: Option<u64>,
+    },
+    /// The ids of the route issuer keys by source,
+    /// the cheqd resource they were fetched from or `inline` for the keys directly provided
+    #[returns(Vec<(String, Vec<String>)>)]
+    RouteKeyIds { app_addr: String, route_id: RouteId },
+    /// The IBC channels to cheqd and the active one the resource requests are sent on
+    #[returns(crate::types::CheqdChannelsResponse)]
+    CheqdChannels {},
 }
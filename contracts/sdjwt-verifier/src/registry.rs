@@ -0,0 +1,468 @@
+use crate::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    state::{
+        APP_ADMINS, APP_COMPROMISED_KEYS, COMPROMISED_KEYS, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT,
+        OWNER, SUSPENDED_ISSUERS, SUSPENDED_KEYS, TRUSTED_ISSUERS,
+    },
+    types::{IssuerSuspension, JwkInfo, KeyCompromise, TrustedIssuer, IDX},
+    verifier::parse_issuer_jwk,
+};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Env, Event, MessageInfo, Order,
+    Response, StdResult, Storage, Timestamp,
+};
+use cw_storage_plus::{Bound, Map};
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// The JWT claim with the credential issuance time, compared to a compromised key cutoff
+pub const IAT_KEY: &str = "iat";
+
+/// Ensures the caller is the contract owner
+pub fn ensure_owner(storage: &dyn Storage, sender: &Addr) -> Result<(), SdjwtVerifierError> {
+    if OWNER.may_load(storage)?.as_ref() != Some(sender) {
+        return Err(SdjwtVerifierError::UnauthorisedCaller);
+    }
+    Ok(())
+}
+
+// Execute message handlers
+pub fn handle_set_trusted_issuer(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    issuer_data: Binary,
+    metadata: Option<Binary>,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    _set_trusted_issuer(deps.storage, name, issuer_data, metadata)
+}
+
+pub fn handle_remove_trusted_issuer(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    _remove_trusted_issuer(deps.storage, name)
+}
+
+pub fn handle_update_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    OWNER.save(deps.storage, &new_owner)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_owner")
+        .add_attribute("owner", new_owner))
+}
+
+pub fn handle_suspend_issuer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    iss: Option<String>,
+    key_thumbprint: Option<String>,
+    reason: Option<String>,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    _suspend_issuer(deps.storage, &env, iss, key_thumbprint, reason)
+}
+
+pub fn handle_reinstate_issuer(
+    deps: DepsMut,
+    info: MessageInfo,
+    iss: Option<String>,
+    key_thumbprint: Option<String>,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    _reinstate_issuer(deps.storage, iss, key_thumbprint)
+}
+
+/// Compromised keys can be set by the app admin for its own routes,
+/// or by the owner for all routes
+pub fn handle_set_key_compromised(
+    deps: DepsMut,
+    info: MessageInfo,
+    app_addr: Option<String>,
+    key_thumbprint: String,
+    cutoff: Timestamp,
+    allowed_idx: Option<Vec<u64>>,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_key_compromise_admin(deps.as_ref(), &info.sender, app_addr.as_deref())?;
+    _set_key_compromised(
+        deps.storage,
+        app_addr.as_deref(),
+        key_thumbprint,
+        KeyCompromise {
+            cutoff,
+            allowed_idx,
+        },
+    )
+}
+
+pub fn handle_clear_key_compromised(
+    deps: DepsMut,
+    info: MessageInfo,
+    app_addr: Option<String>,
+    key_thumbprint: String,
+) -> Result<Response, SdjwtVerifierError> {
+    ensure_key_compromise_admin(deps.as_ref(), &info.sender, app_addr.as_deref())?;
+    _clear_key_compromised(deps.storage, app_addr.as_deref(), key_thumbprint)
+}
+
+fn ensure_key_compromise_admin(
+    deps: Deps,
+    sender: &Addr,
+    app_addr: Option<&str>,
+) -> Result<(), SdjwtVerifierError> {
+    match app_addr {
+        Some(app_addr) => {
+            let app_addr = deps.api.addr_validate(app_addr)?;
+            let app_admin = APP_ADMINS
+                .load(deps.storage, app_addr.as_str())
+                .map_err(|_| SdjwtVerifierError::AppIsNotRegistered)?;
+            if app_admin != sender {
+                return Err(SdjwtVerifierError::UnauthorisedCaller);
+            }
+            Ok(())
+        }
+        None => ensure_owner(deps.storage, sender),
+    }
+}
+
+/// Adds or replaces a registry entry,
+/// this applies to every route that references the entry by `name`
+pub fn _set_trusted_issuer(
+    storage: &mut dyn Storage,
+    name: String,
+    issuer_data: Binary,
+    metadata: Option<Binary>,
+) -> Result<Response, SdjwtVerifierError> {
+    let issuer_info: JwkInfo = from_json(&issuer_data)?;
+    let jwk = parse_issuer_jwk(&issuer_info.jwk)?;
+
+    TRUSTED_ISSUERS.save(
+        storage,
+        &name,
+        &TrustedIssuer {
+            issuer: issuer_info.issuer.clone(),
+            jwk: to_json_binary(&jwk)?,
+            metadata,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_trusted_issuer")
+        .add_attribute("name", name)
+        .add_attribute("issuer", issuer_info.issuer))
+}
+
+pub fn _remove_trusted_issuer(
+    storage: &mut dyn Storage,
+    name: String,
+) -> Result<Response, SdjwtVerifierError> {
+    if !TRUSTED_ISSUERS.has(storage, &name) {
+        return Err(SdjwtVerifierError::TrustedIssuerNotFound(name));
+    }
+    TRUSTED_ISSUERS.remove(storage, &name);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_trusted_issuer")
+        .add_attribute("name", name))
+}
+
+/// Suspends an issuer by `iss` and / or key thumbprint for every app and route
+pub fn _suspend_issuer(
+    storage: &mut dyn Storage,
+    env: &Env,
+    iss: Option<String>,
+    key_thumbprint: Option<String>,
+    reason: Option<String>,
+) -> Result<Response, SdjwtVerifierError> {
+    if iss.is_none() && key_thumbprint.is_none() {
+        return Err(SdjwtVerifierError::SuspensionTargetNotProvided);
+    }
+
+    let suspension = IssuerSuspension {
+        reason: reason.clone(),
+        suspended_at: env.block.time,
+    };
+    let mut event = Event::new("issuer_suspended");
+    if let Some(iss) = iss {
+        SUSPENDED_ISSUERS.save(storage, &iss, &suspension)?;
+        event = event.add_attribute("iss", iss);
+    }
+    if let Some(key_thumbprint) = key_thumbprint {
+        SUSPENDED_KEYS.save(storage, &key_thumbprint, &suspension)?;
+        event = event.add_attribute("key_thumbprint", key_thumbprint);
+    }
+    if let Some(reason) = reason {
+        event = event.add_attribute("reason", reason);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "suspend_issuer")
+        .add_event(event))
+}
+
+pub fn _reinstate_issuer(
+    storage: &mut dyn Storage,
+    iss: Option<String>,
+    key_thumbprint: Option<String>,
+) -> Result<Response, SdjwtVerifierError> {
+    if iss.is_none() && key_thumbprint.is_none() {
+        return Err(SdjwtVerifierError::SuspensionTargetNotProvided);
+    }
+
+    let mut event = Event::new("issuer_reinstated");
+    for (map, key, attr) in [
+        (SUSPENDED_ISSUERS, iss, "iss"),
+        (SUSPENDED_KEYS, key_thumbprint, "key_thumbprint"),
+    ] {
+        if let Some(key) = key {
+            if !map.has(storage, &key) {
+                return Err(SdjwtVerifierError::IssuerNotSuspended(key));
+            }
+            map.remove(storage, &key);
+            event = event.add_attribute(attr, key);
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "reinstate_issuer")
+        .add_event(event))
+}
+
+pub fn _set_key_compromised(
+    storage: &mut dyn Storage,
+    app_addr: Option<&str>,
+    key_thumbprint: String,
+    compromise: KeyCompromise,
+) -> Result<Response, SdjwtVerifierError> {
+    match app_addr {
+        Some(app_addr) => {
+            APP_COMPROMISED_KEYS.save(storage, (app_addr, &key_thumbprint), &compromise)?
+        }
+        None => COMPROMISED_KEYS.save(storage, &key_thumbprint, &compromise)?,
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_key_compromised")
+        .add_attribute("app_addr", app_addr.unwrap_or_default())
+        .add_attribute("key_thumbprint", key_thumbprint)
+        .add_attribute("cutoff", compromise.cutoff.seconds().to_string()))
+}
+
+pub fn _clear_key_compromised(
+    storage: &mut dyn Storage,
+    app_addr: Option<&str>,
+    key_thumbprint: String,
+) -> Result<Response, SdjwtVerifierError> {
+    let found = match app_addr {
+        Some(app_addr) => {
+            let found = APP_COMPROMISED_KEYS.has(storage, (app_addr, &key_thumbprint));
+            APP_COMPROMISED_KEYS.remove(storage, (app_addr, &key_thumbprint));
+            found
+        }
+        None => {
+            let found = COMPROMISED_KEYS.has(storage, &key_thumbprint);
+            COMPROMISED_KEYS.remove(storage, &key_thumbprint);
+            found
+        }
+    };
+    if !found {
+        return Err(SdjwtVerifierError::KeyNotCompromised(key_thumbprint));
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "clear_key_compromised")
+        .add_attribute("app_addr", app_addr.unwrap_or_default())
+        .add_attribute("key_thumbprint", key_thumbprint))
+}
+
+/// Checks the verified claims of a credential signed with a key marked as compromised,
+/// either contract-wide or by the app, against the cutoff and the allowed `idx`
+pub fn ensure_key_not_compromised(
+    storage: &dyn Storage,
+    app_addr: &str,
+    key_thumbprint: &str,
+    claims: &Value,
+) -> Result<(), SdjwtVerifierResultError> {
+    for compromise in [
+        COMPROMISED_KEYS.may_load(storage, key_thumbprint)?,
+        APP_COMPROMISED_KEYS.may_load(storage, (app_addr, key_thumbprint))?,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        // Credentials without an issuance time can not be told apart from forgeries
+        let issued_before_cutoff = claims
+            .get(IAT_KEY)
+            .and_then(Value::as_u64)
+            .is_some_and(|iat| iat < compromise.cutoff.seconds());
+        let idx_allowed = match &compromise.allowed_idx {
+            Some(allowed_idx) => claims
+                .get(IDX)
+                .and_then(Value::as_u64)
+                .is_some_and(|idx| allowed_idx.contains(&idx)),
+            None => true,
+        };
+        if !issued_before_cutoff || !idx_allowed {
+            return Err(SdjwtVerifierResultError::KeyCompromised(
+                key_thumbprint.to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the contract-wide denylist, by `iss` and, if known, the issuer key thumbprint
+pub fn ensure_issuer_not_suspended(
+    storage: &dyn Storage,
+    iss: &str,
+    key_thumbprint: Option<&str>,
+) -> Result<(), SdjwtVerifierResultError> {
+    if SUSPENDED_ISSUERS.has(storage, iss) {
+        return Err(SdjwtVerifierResultError::IssuerSuspended(iss.to_string()));
+    }
+    if let Some(key_thumbprint) = key_thumbprint {
+        if SUSPENDED_KEYS.has(storage, key_thumbprint) {
+            return Err(SdjwtVerifierResultError::IssuerSuspended(
+                key_thumbprint.to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The RFC 7638 thumbprint of an Ed25519 key given its base64url encoded public key `x`
+pub fn ed25519_thumbprint(x: &str) -> String {
+    // The required members in lexicographic order, without whitespace
+    let canonical = format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{x}"}}"#);
+    to_base64url(&Sha256::digest(canonical.as_bytes()))
+}
+
+/// The RFC 7638 thumbprint of an issuer JWK, only Ed25519 keys are supported
+pub fn jwk_thumbprint(jwk: &Jwk) -> Option<String> {
+    match &jwk.algorithm {
+        AlgorithmParameters::OctetKeyPair(params) => Some(ed25519_thumbprint(&params.x)),
+        _ => None,
+    }
+}
+
+/// Unpadded base64url encoding, as used in JOSE
+pub fn to_base64url(data: &[u8]) -> String {
+    Binary::from(data)
+        .to_base64()
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_")
+}
+
+/// Decodes unpadded or padded base64url, as used in JOSE
+pub fn from_base64url(data: &str) -> StdResult<Binary> {
+    let padding = (4 - data.len() % 4) % 4;
+    let b64 = data.replace('-', "+").replace('_', "/") + &"=".repeat(padding);
+    Binary::from_base64(&b64)
+}
+
+/// Finds the key for `iss` in the registry entries referenced by a route
+pub fn registry_issuer_pubkey(
+    storage: &dyn Storage,
+    names: &[String],
+    iss: &str,
+) -> Result<Option<Jwk>, SdjwtVerifierResultError> {
+    for name in names {
+        if let Some(entry) = TRUSTED_ISSUERS.may_load(storage, name)? {
+            if entry.issuer == iss {
+                return Ok(Some(from_json(&entry.jwk)?));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// Query handlers
+pub fn query_owner(deps: Deps) -> Result<String, SdjwtVerifierError> {
+    Ok(OWNER.load(deps.storage)?.to_string())
+}
+
+pub fn query_trusted_issuer(deps: Deps, name: String) -> Result<TrustedIssuer, SdjwtVerifierError> {
+    TRUSTED_ISSUERS
+        .may_load(deps.storage, &name)?
+        .ok_or(SdjwtVerifierError::TrustedIssuerNotFound(name))
+}
+
+pub fn query_trusted_issuers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<(String, TrustedIssuer)>, SdjwtVerifierError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    Ok(TRUSTED_ISSUERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+pub fn query_suspended_issuers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<(String, IssuerSuspension)>, SdjwtVerifierError> {
+    query_suspensions(deps, SUSPENDED_ISSUERS, start_after, limit)
+}
+
+pub fn query_suspended_keys(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<(String, IssuerSuspension)>, SdjwtVerifierError> {
+    query_suspensions(deps, SUSPENDED_KEYS, start_after, limit)
+}
+
+fn query_suspensions(
+    deps: Deps,
+    map: Map<&str, IssuerSuspension>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<(String, IssuerSuspension)>, SdjwtVerifierError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    Ok(map
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+pub fn query_compromised_keys(
+    deps: Deps,
+    app_addr: Option<String>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<(String, KeyCompromise)>, SdjwtVerifierError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let keys = match app_addr {
+        Some(app_addr) => APP_COMPROMISED_KEYS
+            .prefix(&app_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<Result<Vec<_>, _>>()?,
+        None => COMPROMISED_KEYS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    Ok(keys)
+}
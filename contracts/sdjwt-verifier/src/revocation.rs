@@ -0,0 +1,691 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use avida_common::types::{IdxRange, RouteId};
+use cosmwasm_std::{
+    Addr, Binary, BlockInfo, DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Storage,
+};
+use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    state::{
+        REVOCATION_CHUNKS, REVOCATION_DELEGATES, REVOCATION_ROOTS, SHARED_REVOCATION_CHUNKS,
+        SHARED_REVOCATION_LISTS,
+    },
+    types::{
+        Criterion, RevocationDelegate, RevocationRoot, SharedRevocationList, SmtNonMembershipProof,
+        VerificationRequirements, IDX,
+    },
+};
+
+/// Number of `idx` covered by a single revocation bitmap chunk
+pub const REVOCATION_CHUNK_BITS: u64 = 2048;
+
+/// Depth of the revocation sparse Merkle tree, one leaf per `u64` idx
+pub const SMT_DEPTH: usize = 64;
+
+/// Number of revocation roots kept per route
+pub const MAX_REVOCATION_ROOT_HISTORY: usize = 5;
+
+/// Seconds a replaced revocation root is still accepted,
+/// so that proofs made just before a root update still verify
+pub const REVOCATION_ROOT_GRACE_PERIOD: u64 = 600;
+
+/// Maximum number of `idx` updated by a single revocation list update
+pub const MAX_REVOCATION_UPDATE_LEN: u64 = 1 << 20;
+
+const DEFAULT_REVOCATION_LIST_LIMIT: u32 = 100;
+const MAX_REVOCATION_LIST_LIMIT: u32 = 1000;
+
+const REVOCATION_CHUNK_BYTES: usize = (REVOCATION_CHUNK_BITS / 8) as usize;
+
+/// The `iss` key of the revocations applying to all the issuers of the route
+const ALL_ISSUERS: &str = "";
+
+/// Checks if `idx` is revoked in the route revocation list of `iss`,
+/// or in the one of all the route issuers if `iss` is `None`. This is a single storage read
+pub fn is_revoked(
+    storage: &dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    iss: Option<&str>,
+    idx: u64,
+) -> StdResult<bool> {
+    Bitmap::route(app_addr, route_id, iss).is_set(storage, idx)
+}
+
+/// Checks if a credential of `iss` is revoked on the route,
+/// either for all the route issuers or by a revocation scoped to `iss`
+pub fn is_credential_revoked(
+    storage: &dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    iss: &str,
+    idx: u64,
+) -> StdResult<bool> {
+    Ok(is_revoked(storage, app_addr, route_id, None, idx)?
+        || is_revoked(storage, app_addr, route_id, Some(iss), idx)?)
+}
+
+/// Sets the revocation status of the `idx` ranges in the route revocation list of `iss`
+/// (all the route issuers if `None`) in order, so the last update of an `idx` takes precedence
+pub fn update_revocations(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    iss: Option<&str>,
+    updates: &[(IdxRange, bool)],
+) -> StdResult<()> {
+    Bitmap::route(app_addr, route_id, iss).update(storage, updates)
+}
+
+/// Ensures the ranges are valid and the update is not larger than `MAX_REVOCATION_UPDATE_LEN`
+pub fn validate_revocation_updates(updates: &[(IdxRange, bool)]) -> Result<(), SdjwtVerifierError> {
+    let mut len: u64 = 0;
+    for (IdxRange { start, end }, _) in updates {
+        if start > end {
+            return Err(SdjwtVerifierError::RevocationRangeInvalid(*start, *end));
+        }
+        len = len.saturating_add(end - start).saturating_add(1);
+    }
+    if len > MAX_REVOCATION_UPDATE_LEN {
+        return Err(SdjwtVerifierError::RevocationUpdateTooLarge(
+            MAX_REVOCATION_UPDATE_LEN,
+        ));
+    }
+    Ok(())
+}
+
+/// The revoked `idx` of the route revocation list of `iss`
+/// (all the route issuers if `None`) in ascending order
+pub fn query_revocation_list(
+    storage: &dyn Storage,
+    app_addr: String,
+    route_id: RouteId,
+    iss: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<u64>> {
+    Bitmap::route(&app_addr, route_id, iss.as_deref()).list(storage, start_after, limit)
+}
+
+/// Removes all the revocations and revocation delegates of the route
+pub fn clear_revocations(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+) -> StdResult<()> {
+    let chunks = REVOCATION_CHUNKS
+        .sub_prefix((app_addr, route_id))
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (iss, chunk_id) in chunks {
+        REVOCATION_CHUNKS.remove(storage, ((app_addr, route_id), &iss, chunk_id));
+    }
+
+    let delegates = REVOCATION_DELEGATES
+        .prefix((app_addr, route_id))
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for delegate in delegates {
+        REVOCATION_DELEGATES.remove(storage, (app_addr, route_id, &delegate));
+    }
+    Ok(())
+}
+
+/// Grants `delegate` the right to update the route revocations of the credentials of `iss`
+pub fn set_revocation_delegate(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    delegate: &Addr,
+    iss: &str,
+) -> Result<(), SdjwtVerifierError> {
+    if iss == ALL_ISSUERS {
+        return Err(SdjwtVerifierError::RevocationDelegateIssuerEmpty);
+    }
+    REVOCATION_DELEGATES.save(
+        storage,
+        (app_addr, route_id, delegate.as_str()),
+        &iss.to_string(),
+    )?;
+    Ok(())
+}
+
+/// The issuer `iss` whose revocations `delegate` can update on the route, if any
+pub fn revocation_delegate_issuer(
+    storage: &dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    delegate: &Addr,
+) -> StdResult<Option<String>> {
+    REVOCATION_DELEGATES.may_load(storage, (app_addr, route_id, delegate.as_str()))
+}
+
+pub fn remove_revocation_delegate(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    delegate: &Addr,
+) {
+    REVOCATION_DELEGATES.remove(storage, (app_addr, route_id, delegate.as_str()));
+}
+
+pub fn query_revocation_delegates(
+    storage: &dyn Storage,
+    app_addr: String,
+    route_id: RouteId,
+) -> StdResult<Vec<RevocationDelegate>> {
+    REVOCATION_DELEGATES
+        .prefix((&app_addr, route_id))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(delegate, iss)| RevocationDelegate { delegate, iss }))
+        .collect()
+}
+
+/// The updates of a revocation list update request, revocations are applied before unrevocations
+pub fn revocation_updates(
+    revoke: Vec<u64>,
+    unrevoke: Vec<u64>,
+    revoke_ranges: Vec<IdxRange>,
+    unrevoke_ranges: Vec<IdxRange>,
+) -> Vec<(IdxRange, bool)> {
+    revoke
+        .into_iter()
+        .map(IdxRange::from)
+        .chain(revoke_ranges)
+        .map(|range| (range, true))
+        .chain(
+            unrevoke
+                .into_iter()
+                .map(IdxRange::from)
+                .chain(unrevoke_ranges)
+                .map(|range| (range, false)),
+        )
+        .collect()
+}
+
+// Shared revocation list execute message handlers
+pub fn handle_create_shared_revocation_list(
+    deps: DepsMut,
+    info: MessageInfo,
+    list_id: String,
+    editors: Vec<String>,
+) -> Result<Response, SdjwtVerifierError> {
+    if SHARED_REVOCATION_LISTS.has(deps.storage, &list_id) {
+        return Err(SdjwtVerifierError::SharedRevocationListAlreadyExists(
+            list_id,
+        ));
+    }
+    let editors = editors
+        .iter()
+        .map(|editor| deps.api.addr_validate(editor))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    SHARED_REVOCATION_LISTS.save(
+        deps.storage,
+        &list_id,
+        &SharedRevocationList {
+            owner: info.sender.clone(),
+            editors,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_shared_revocation_list")
+        .add_attribute("list_id", list_id)
+        .add_attribute("owner", info.sender))
+}
+
+pub fn handle_update_shared_revocation_list_acl(
+    deps: DepsMut,
+    info: MessageInfo,
+    list_id: String,
+    new_owner: Option<String>,
+    add_editors: Vec<String>,
+    remove_editors: Vec<String>,
+) -> Result<Response, SdjwtVerifierError> {
+    let mut list = load_shared_revocation_list(deps.storage, &list_id)?;
+    if list.owner != info.sender {
+        return Err(SdjwtVerifierError::UnauthorisedCaller);
+    }
+
+    if let Some(new_owner) = new_owner {
+        list.owner = deps.api.addr_validate(&new_owner)?;
+    }
+    for editor in remove_editors {
+        let editor = deps.api.addr_validate(&editor)?;
+        list.editors.retain(|e| *e != editor);
+    }
+    for editor in add_editors {
+        let editor = deps.api.addr_validate(&editor)?;
+        if !list.editors.contains(&editor) {
+            list.editors.push(editor);
+        }
+    }
+    SHARED_REVOCATION_LISTS.save(deps.storage, &list_id, &list)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_shared_revocation_list_acl")
+        .add_attribute("list_id", list_id)
+        .add_attribute("owner", list.owner))
+}
+
+pub fn handle_update_shared_revocation_list(
+    deps: DepsMut,
+    info: MessageInfo,
+    list_id: String,
+    updates: Vec<(IdxRange, bool)>,
+) -> Result<Response, SdjwtVerifierError> {
+    let list = load_shared_revocation_list(deps.storage, &list_id)?;
+    if list.owner != info.sender && !list.editors.contains(&info.sender) {
+        return Err(SdjwtVerifierError::UnauthorisedCaller);
+    }
+
+    validate_revocation_updates(&updates)?;
+    Bitmap::Shared(&list_id).update(deps.storage, &updates)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_shared_revocation_list")
+        .add_attribute("list_id", list_id))
+}
+
+fn load_shared_revocation_list(
+    storage: &dyn Storage,
+    list_id: &str,
+) -> Result<SharedRevocationList, SdjwtVerifierError> {
+    SHARED_REVOCATION_LISTS
+        .may_load(storage, list_id)?
+        .ok_or_else(|| SdjwtVerifierError::SharedRevocationListNotFound(list_id.to_string()))
+}
+
+/// Ensures the shared revocation lists referenced by the requirements exist
+pub fn ensure_shared_revocation_lists_exist(
+    storage: &dyn Storage,
+    requirements: &VerificationRequirements,
+) -> Result<(), SdjwtVerifierError> {
+    for req in requirements.presentation_required.iter() {
+        if let Criterion::NotInRevocationList(list_id) = &req.criterion {
+            load_shared_revocation_list(storage, list_id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks if `idx` is revoked in the shared revocation list, this is a single storage read
+pub fn is_revoked_in_shared_list(
+    storage: &dyn Storage,
+    list_id: &str,
+    idx: u64,
+) -> StdResult<bool> {
+    Bitmap::Shared(list_id).is_set(storage, idx)
+}
+
+pub fn query_shared_revocation_list(
+    storage: &dyn Storage,
+    list_id: String,
+) -> StdResult<SharedRevocationList> {
+    SHARED_REVOCATION_LISTS.load(storage, &list_id)
+}
+
+/// The revoked `idx` of the shared revocation list in ascending order
+pub fn query_shared_revocation_list_entries(
+    storage: &dyn Storage,
+    list_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<u64>> {
+    Bitmap::Shared(&list_id).list(storage, start_after, limit)
+}
+
+/// The `idx` revocation list of the route requirements, if the route checks one
+fn inline_revocation_list(requirements: &mut VerificationRequirements) -> Option<&mut Vec<u64>> {
+    requirements
+        .presentation_required
+        .iter_mut()
+        .find(|req| req.attribute == IDX)
+        .and_then(|req| match &mut req.criterion {
+            Criterion::NotContainedIn(revocation_list) => Some(revocation_list),
+            _ => None,
+        })
+}
+
+/// Takes the `idx` revocation list out of the route requirements,
+/// leaving an empty `Criterion::NotContainedIn` so the route still checks revocations
+pub fn take_inline_revocations(requirements: &mut VerificationRequirements) -> Vec<u64> {
+    inline_revocation_list(requirements)
+        .map(std::mem::take)
+        .unwrap_or_default()
+}
+
+/// Replaces the revocations of all the route issuers with the `revoked` idx,
+/// the revocations scoped to an issuer are kept
+pub fn replace_revocations(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    revoked: &[u64],
+) -> StdResult<()> {
+    let bitmap = Bitmap::route(app_addr, route_id, None);
+    bitmap.clear(storage)?;
+    let updates: Vec<(IdxRange, bool)> = revoked
+        .iter()
+        .map(|idx| (IdxRange::from(*idx), true))
+        .collect();
+    bitmap.update(storage, &updates)
+}
+
+/// Fills the `idx` revocation list of the route requirements
+/// with the revocations of all the route issuers
+pub fn restore_inline_revocations(
+    storage: &dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    requirements: &mut VerificationRequirements,
+) -> StdResult<()> {
+    if let Some(revocation_list) = inline_revocation_list(requirements) {
+        *revocation_list =
+            Bitmap::route(app_addr, route_id, None).set_idx(storage, 0, usize::MAX)?;
+    }
+    Ok(())
+}
+
+/// Commits a new revocation root for the route, keeping the most recent ones
+pub fn _update_revocation_root(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: &str,
+    route_id: RouteId,
+    root: Binary,
+) -> Result<Event, SdjwtVerifierError> {
+    if root.len() != 32 {
+        return Err(SdjwtVerifierError::RevocationRootInvalid);
+    }
+
+    let mut roots = REVOCATION_ROOTS
+        .may_load(storage, (app_addr, route_id))?
+        .unwrap_or_default();
+    roots.insert(
+        0,
+        RevocationRoot {
+            root: root.clone(),
+            committed_at: env.block.time,
+        },
+    );
+    roots.truncate(MAX_REVOCATION_ROOT_HISTORY);
+    REVOCATION_ROOTS.save(storage, (app_addr, route_id), &roots)?;
+
+    Ok(Event::new("revocation_root_updated")
+        .add_attribute("app_addr", app_addr)
+        .add_attribute("route_id", route_id.to_string())
+        .add_attribute("root", root.to_hex()))
+}
+
+/// Removes the revocation roots of the route
+pub fn clear_revocation_roots(storage: &mut dyn Storage, app_addr: &str, route_id: RouteId) {
+    REVOCATION_ROOTS.remove(storage, (app_addr, route_id));
+}
+
+/// Checks the non-membership proof of `idx` against the route revocation roots.
+/// The most recent root is always accepted,
+/// older ones only within `REVOCATION_ROOT_GRACE_PERIOD` of being replaced
+pub fn check_not_in_revocation_root(
+    storage: &dyn Storage,
+    block_info: &BlockInfo,
+    app_addr: &str,
+    route_id: RouteId,
+    idx: u64,
+    proof: Option<&SmtNonMembershipProof>,
+) -> Result<(), SdjwtVerifierResultError> {
+    let roots = REVOCATION_ROOTS
+        .may_load(storage, (app_addr, route_id))?
+        .ok_or(SdjwtVerifierResultError::RevocationRootNotCommitted)?;
+    let proof = proof.ok_or(SdjwtVerifierResultError::RevocationProofNotProvided)?;
+
+    let computed = smt_empty_leaf_root(idx, proof)?;
+
+    let now = block_info.time.seconds();
+    let mut replaced_at: Option<u64> = None;
+    for RevocationRoot { root, committed_at } in roots {
+        if replaced_at.is_some_and(|t| now > t + REVOCATION_ROOT_GRACE_PERIOD) {
+            break;
+        }
+        if root.as_slice() == computed {
+            return Ok(());
+        }
+        replaced_at = Some(committed_at.seconds());
+    }
+
+    Err(SdjwtVerifierResultError::RevocationProofRootMismatch(idx))
+}
+
+pub fn query_revocation_roots(
+    storage: &dyn Storage,
+    app_addr: String,
+    route_id: RouteId,
+) -> StdResult<Vec<RevocationRoot>> {
+    Ok(REVOCATION_ROOTS
+        .may_load(storage, (&app_addr, route_id))?
+        .unwrap_or_default())
+}
+
+/// A revocation bitmap, stored in chunks of `REVOCATION_CHUNK_BITS`
+#[derive(Clone, Copy)]
+enum Bitmap<'a> {
+    /// The route revocations of an issuer `iss`, or of all the route issuers
+    Route((&'a str, RouteId), &'a str),
+    /// A shared revocation list by its id
+    Shared(&'a str),
+}
+
+impl<'a> Bitmap<'a> {
+    fn route(app_addr: &'a str, route_id: RouteId, iss: Option<&'a str>) -> Self {
+        Bitmap::Route((app_addr, route_id), iss.unwrap_or(ALL_ISSUERS))
+    }
+
+    fn load(self, storage: &dyn Storage, chunk_id: u64) -> StdResult<Option<Binary>> {
+        match self {
+            Bitmap::Route(route, iss) => {
+                REVOCATION_CHUNKS.may_load(storage, (route, iss, chunk_id))
+            }
+            Bitmap::Shared(list_id) => {
+                SHARED_REVOCATION_CHUNKS.may_load(storage, (list_id, chunk_id))
+            }
+        }
+    }
+
+    fn save(self, storage: &mut dyn Storage, chunk_id: u64, chunk: &Binary) -> StdResult<()> {
+        match self {
+            Bitmap::Route(route, iss) => {
+                REVOCATION_CHUNKS.save(storage, (route, iss, chunk_id), chunk)
+            }
+            Bitmap::Shared(list_id) => {
+                SHARED_REVOCATION_CHUNKS.save(storage, (list_id, chunk_id), chunk)
+            }
+        }
+    }
+
+    fn remove(self, storage: &mut dyn Storage, chunk_id: u64) {
+        match self {
+            Bitmap::Route(route, iss) => REVOCATION_CHUNKS.remove(storage, (route, iss, chunk_id)),
+            Bitmap::Shared(list_id) => {
+                SHARED_REVOCATION_CHUNKS.remove(storage, (list_id, chunk_id))
+            }
+        }
+    }
+
+    /// The stored chunks in ascending order, starting from `first_chunk_id`
+    fn chunks<'s>(
+        self,
+        storage: &'s dyn Storage,
+        first_chunk_id: u64,
+    ) -> Box<dyn Iterator<Item = StdResult<(u64, Binary)>> + 's> {
+        let min = Some(Bound::inclusive(first_chunk_id));
+        match self {
+            Bitmap::Route(route, iss) => {
+                REVOCATION_CHUNKS
+                    .prefix((route, iss))
+                    .range(storage, min, None, Order::Ascending)
+            }
+            Bitmap::Shared(list_id) => {
+                SHARED_REVOCATION_CHUNKS
+                    .prefix(list_id)
+                    .range(storage, min, None, Order::Ascending)
+            }
+        }
+    }
+
+    /// Removes all the chunks
+    fn clear(self, storage: &mut dyn Storage) -> StdResult<()> {
+        let chunk_ids = self
+            .chunks(storage, 0)
+            .map(|chunk| chunk.map(|(chunk_id, _)| chunk_id))
+            .collect::<StdResult<Vec<_>>>()?;
+        for chunk_id in chunk_ids {
+            self.remove(storage, chunk_id);
+        }
+        Ok(())
+    }
+
+    /// Checks if `idx` is set, this is a single storage read
+    fn is_set(self, storage: &dyn Storage, idx: u64) -> StdResult<bool> {
+        let (chunk_id, byte, mask) = bit_position(idx);
+        Ok(self
+            .load(storage, chunk_id)?
+            .is_some_and(|chunk| chunk[byte] & mask != 0))
+    }
+
+    /// Sets or clears the `idx` ranges in order, so the last update of an `idx` takes precedence
+    fn update(self, storage: &mut dyn Storage, updates: &[(IdxRange, bool)]) -> StdResult<()> {
+        let mut chunks: BTreeMap<u64, Vec<u8>> = BTreeMap::new();
+
+        for (IdxRange { start, end }, revoked) in updates {
+            let mut first = *start;
+            loop {
+                let chunk_id = first / REVOCATION_CHUNK_BITS;
+                let last =
+                    (*end).min(chunk_id * REVOCATION_CHUNK_BITS + (REVOCATION_CHUNK_BITS - 1));
+                let chunk = match chunks.entry(chunk_id) {
+                    Entry::Occupied(e) => e.into_mut(),
+                    Entry::Vacant(e) => e.insert(
+                        self.load(storage, chunk_id)?
+                            .map(|chunk| chunk.to_vec())
+                            .unwrap_or_else(|| vec![0; REVOCATION_CHUNK_BYTES]),
+                    ),
+                };
+                for idx in first..=last {
+                    let (_, byte, mask) = bit_position(idx);
+                    if *revoked {
+                        chunk[byte] |= mask;
+                    } else {
+                        chunk[byte] &= !mask;
+                    }
+                }
+                if last == *end {
+                    break;
+                }
+                first = last + 1;
+            }
+        }
+
+        // Empty chunks are not kept in storage
+        for (chunk_id, chunk) in chunks {
+            if chunk.iter().all(|b| *b == 0) {
+                self.remove(storage, chunk_id);
+            } else {
+                self.save(storage, chunk_id, &Binary::from(chunk))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The set `idx` in ascending order
+    fn list(
+        self,
+        storage: &dyn Storage,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<u64>> {
+        let limit = limit
+            .unwrap_or(DEFAULT_REVOCATION_LIST_LIMIT)
+            .min(MAX_REVOCATION_LIST_LIMIT) as usize;
+        let start = match start_after {
+            Some(u64::MAX) => return Ok(vec![]),
+            Some(idx) => idx + 1,
+            None => 0,
+        };
+        self.set_idx(storage, start, limit)
+    }
+
+    /// The first `limit` set `idx` from `start` in ascending order
+    fn set_idx(self, storage: &dyn Storage, start: u64, limit: usize) -> StdResult<Vec<u64>> {
+        let mut revoked = Vec::new();
+        for chunk in self.chunks(storage, start / REVOCATION_CHUNK_BITS) {
+            let (chunk_id, chunk) = chunk?;
+            for (byte, bits) in chunk.iter().enumerate().filter(|(_, bits)| **bits != 0) {
+                for bit in (0..8).filter(|bit| bits & (1 << bit) != 0) {
+                    let idx = chunk_id * REVOCATION_CHUNK_BITS + byte as u64 * 8 + bit;
+                    if idx < start {
+                        continue;
+                    }
+                    revoked.push(idx);
+                    if revoked.len() == limit {
+                        return Ok(revoked);
+                    }
+                }
+            }
+        }
+        Ok(revoked)
+    }
+}
+
+/// Computes the root of the tree where the leaf of `idx` is empty, given its siblings
+fn smt_empty_leaf_root(
+    idx: u64,
+    proof: &SmtNonMembershipProof,
+) -> Result<[u8; 32], SdjwtVerifierResultError> {
+    if proof.siblings.len() != SMT_DEPTH {
+        return Err(SdjwtVerifierResultError::RevocationProofInvalid(format!(
+            "expected {SMT_DEPTH} siblings"
+        )));
+    }
+
+    let mut node = [0u8; 32];
+    // The root of an empty subtree at the current level
+    let mut empty = [0u8; 32];
+    for (level, sibling) in proof.siblings.iter().enumerate() {
+        let sibling: [u8; 32] = match sibling {
+            Some(sibling) => sibling.as_slice().try_into().map_err(|_| {
+                SdjwtVerifierResultError::RevocationProofInvalid(format!(
+                    "sibling {level} must be 32 bytes"
+                ))
+            })?,
+            None => empty,
+        };
+        node = if (idx >> level) & 1 == 0 {
+            hash_pair(&node, &sibling)
+        } else {
+            hash_pair(&sibling, &node)
+        };
+        empty = hash_pair(&empty, &empty);
+    }
+    Ok(node)
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+fn bit_position(idx: u64) -> (u64, usize, u8) {
+    let bit = idx % REVOCATION_CHUNK_BITS;
+    (
+        idx / REVOCATION_CHUNK_BITS,
+        (bit / 8) as usize,
+        1 << (bit % 8),
+    )
+}
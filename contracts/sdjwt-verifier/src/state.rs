@@ -1,16 +1,98 @@
 // State structure
 
+use avida_cheqd::{
+    did_url::CheqdNetwork,
+    types::{ResourceReqPacket, ResourceWithMetadata},
+};
 use avida_common::types::RouteId;
-use cosmwasm_std::Addr;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, Empty};
+use cw_storage_plus::{Item, Map, SnapshotMap, Strategy};
 
-use crate::types::{PendingRoute, VerificationRequirements};
+use crate::types::{
+    CheqdChannel, FetchFailure, IssuerKeys, IssuerSuspension, KeyCompromise, PendingRoute,
+    ResolvedKeyVersion, RevocationRoot, RouteKeySource, SharedRevocationList, StatusList,
+    TrustedIssuer, VerificationRequirements,
+};
+
+pub const DEFAULT_PAGE_LIMIT: u32 = 10;
+pub const MAX_PAGE_LIMIT: u32 = 30;
 
 // Map of the app addr and route id to the verification requirements
 pub const APP_ROUTES_REQUIREMENTS: Map<(String, RouteId), VerificationRequirements> =
     Map::new("app_routes_requirements");
 
+// Index of the routes trusting an issuer, by `iss`, app addr and route id
+pub const ISSUER_ROUTES: Map<(&str, &str, RouteId), Empty> = Map::new("issuer_routes");
+
+// Revoked `idx` of the routes as bitmaps, by app addr and route id, issuer `iss`
+// (empty for the revocations of all the route issuers) and chunk number
+pub const REVOCATION_CHUNKS: Map<((&str, RouteId), &str, u64), Binary> =
+    Map::new("revocation_chunks");
+// Addresses allowed to update the route revocations of an issuer,
+// by app addr, route id and delegate addr, to the issuer `iss`
+pub const REVOCATION_DELEGATES: Map<(&str, RouteId, &str), String> =
+    Map::new("revocation_delegates");
+// Revocation lists shared by routes, by list id
+pub const SHARED_REVOCATION_LISTS: Map<&str, SharedRevocationList> =
+    Map::new("shared_revocation_lists");
+// Revoked `idx` of the shared revocation lists as bitmaps, by list id and chunk number
+pub const SHARED_REVOCATION_CHUNKS: Map<(&str, u64), Binary> = Map::new("shared_revocation_chunks");
+// Committed revocation roots of the routes, most recent first, by app addr and route id
+pub const REVOCATION_ROOTS: Map<(&str, RouteId), Vec<RevocationRoot>> =
+    Map::new("revocation_roots");
+
 pub const APP_ADMINS: Map<&str, Addr> = Map::new("app_admins");
+// The active channel the resource requests are sent on
 pub const CHANNEL_ID: Item<String> = Item::new("channel_id");
-pub const PENDING_VERIFICATION_REQ_REQUESTS: Map<&str, PendingRoute> =
+// The channels to cheqd connected to the contract, by channel id
+pub const CHEQD_CHANNELS: Map<&str, CheqdChannel> = Map::new("cheqd_channels");
+// The cheqd network the channel is connected to, if set the registered DID resources must be on it
+pub const CHEQD_NETWORK: Item<CheqdNetwork> = Item::new("cheqd_network");
+// The routes subscribed to the in flight resource requests, by resource (`collection_id:resource_id`)
+pub const PENDING_VERIFICATION_REQ_REQUESTS: Map<&str, Vec<PendingRoute>> =
+    Map::new("pending_resource_requests");
+// The in flight resource requests stored by earlier versions, with a single route
+pub const LEGACY_PENDING_REQUESTS: Map<&str, PendingRoute> =
     Map::new("pending_verification_req_requests");
+// Resource requests made before the IBC channel was connected, to be sent once it is,
+// by resource (`collection_id:resource_id`)
+pub const QUEUED_RESOURCE_REQUESTS: Map<&str, ResourceReqPacket> =
+    Map::new("queued_resource_requests");
+// Contract-wide cache of the fetched cheqd resources, by resource (`collection_id:resource_id`)
+pub const RESOURCE_CACHE: Map<&str, ResourceWithMetadata> = Map::new("resource_cache");
+// Failed IBC resource fetches of the routes, by app addr, route id and resource (`collection_id:resource_id`)
+pub const FETCH_FAILURES: Map<(&str, RouteId, &str), FetchFailure> = Map::new("fetch_failures");
+// The cheqd resources the issuer keys of the routes are fetched from, by app addr, route id and resource
+pub const ROUTE_KEY_SOURCES: Map<(&str, RouteId, &str), RouteKeySource> =
+    Map::new("route_key_sources");
+// The issuer keys of the routes by source, the cheqd resource (`collection_id:resource_id`)
+// they were fetched from or `INLINE_KEYS_SOURCE` for the keys directly provided
+pub const ROUTE_SOURCE_KEYS: Map<(&str, RouteId, &str), IssuerKeys> = Map::new("route_source_keys");
+// The cheqd resource versions the route keys were taken from, kept for every height
+pub const ROUTE_KEY_VERSIONS: SnapshotMap<(&str, RouteId), Vec<ResolvedKeyVersion>> =
+    SnapshotMap::new(
+        "route_key_versions",
+        "route_key_versions__checkpoints",
+        "route_key_versions__changelog",
+        Strategy::EveryBlock,
+    );
+// Failed fetches scheduled for a retry, by retry time (seconds), app addr, route id and resource
+pub const FETCH_RETRY_QUEUE: Map<(u64, (&str, RouteId), &str), Empty> =
+    Map::new("fetch_retry_queue");
+
+// The contract owner, manages the contract-wide issuer registry
+pub const OWNER: Item<Addr> = Item::new("owner");
+// Contract-wide registry of trusted issuers by the registry entry name
+pub const TRUSTED_ISSUERS: Map<&str, TrustedIssuer> = Map::new("trusted_issuers");
+// Contract-wide denylist of issuers by `iss`, checked before any route level key lookup
+pub const SUSPENDED_ISSUERS: Map<&str, IssuerSuspension> = Map::new("suspended_issuers");
+// Contract-wide denylist of issuer keys by their RFC 7638 thumbprint
+pub const SUSPENDED_KEYS: Map<&str, IssuerSuspension> = Map::new("suspended_keys");
+// Contract-wide compromised issuer keys by their RFC 7638 thumbprint
+pub const COMPROMISED_KEYS: Map<&str, KeyCompromise> = Map::new("compromised_keys");
+// Compromised issuer keys set by app admins, by the app addr and key thumbprint
+pub const APP_COMPROMISED_KEYS: Map<(&str, &str), KeyCompromise> = Map::new("app_compromised_keys");
+// Verified Token Status Lists of the routes, by app addr and route id, the issuer `iss` and the list uri.
+// They are verified with the route issuer keys, so a list is only trusted by the route it is stored for
+pub const STATUS_LISTS: Map<((&str, RouteId), &str, &str), StatusList> =
+    Map::new("route_status_lists");
@@ -0,0 +1,204 @@
+use crate::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    registry::from_base64url,
+    state::STATUS_LISTS,
+    types::{StatusList, StatusListCriterion, VerificationRequirements},
+    verifier::issuer_pubkey,
+};
+use avida_common::types::RouteId;
+use cosmwasm_std::{from_json, BlockInfo, Deps, Event, Order, StdResult, Storage};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The `typ` header of a Token Status List JWT
+pub const STATUS_LIST_JWT_TYP: &str = "statuslist+jwt";
+
+/// Maximum size of a decompressed status list, this is 8M 1-bit statuses
+pub const MAX_STATUS_LIST_LEN: usize = 1024 * 1024;
+
+/// The status of a valid credential, all others are rejected unless accepted by the route
+pub const STATUS_VALID: u8 = 0x00;
+/// The status of a revoked credential
+pub const STATUS_INVALID: u8 = 0x01;
+/// The status of a temporarily suspended credential
+pub const STATUS_SUSPENDED: u8 = 0x02;
+
+#[derive(Deserialize)]
+struct StatusListTokenClaims {
+    sub: String,
+    iat: u64,
+    exp: Option<u64>,
+    status_list: StatusListClaim,
+}
+
+#[derive(Deserialize)]
+struct StatusListClaim {
+    bits: u8,
+    lst: String,
+}
+
+/// The `status_list` of the credential `status` claim
+#[derive(Deserialize)]
+struct StatusListReference {
+    idx: u64,
+    uri: String,
+}
+
+#[derive(Deserialize)]
+struct StatusClaim {
+    status_list: StatusListReference,
+}
+
+/// Verifies a Token Status List JWT with the key of its issuer on the route
+/// and stores the decompressed list for the route, unless the same or a newer list is already stored
+pub fn _update_status_list(
+    storage: &mut dyn Storage,
+    block_info: &BlockInfo,
+    app_addr: &str,
+    route_id: RouteId,
+    requirements: &VerificationRequirements,
+    token: &[u8],
+) -> Result<Event, SdjwtVerifierError> {
+    let token = std::str::from_utf8(token)
+        .map_err(|e| SdjwtVerifierError::StatusListTokenInvalid(e.to_string()))?;
+
+    let header = decode_header(token)
+        .map_err(|e| SdjwtVerifierError::StatusListTokenInvalid(e.to_string()))?;
+    if header.typ.as_deref() != Some(STATUS_LIST_JWT_TYP) {
+        return Err(SdjwtVerifierError::StatusListTokenInvalid(format!(
+            "typ must be {STATUS_LIST_JWT_TYP}"
+        )));
+    }
+
+    // The issuer is needed to find the key before the signature can be verified
+    let payload = token.split('.').nth(1).unwrap_or_default();
+    let payload: Value = from_json(from_base64url(payload)?)?;
+    let iss = payload.get("iss").and_then(Value::as_str).ok_or(
+        SdjwtVerifierError::StatusListTokenInvalid("iss not found".to_string()),
+    )?;
+
+    let pubkey = issuer_pubkey(storage, requirements, iss, header.kid.as_deref())
+        .map_err(SdjwtVerifierError::SdjwtVerifierResultError)?;
+    let decoding_key = DecodingKey::from_jwk(&pubkey)
+        .map_err(|e| SdjwtVerifierError::StatusListTokenInvalid(e.to_string()))?;
+
+    // Expiry is checked against the block time below
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+    let claims = decode::<StatusListTokenClaims>(token, &decoding_key, &validation)
+        .map_err(|e| SdjwtVerifierError::StatusListTokenInvalid(e.to_string()))?
+        .claims;
+
+    if claims
+        .exp
+        .is_some_and(|exp| exp <= block_info.time.seconds())
+    {
+        return Err(SdjwtVerifierError::StatusListTokenInvalid(
+            "expired".to_string(),
+        ));
+    }
+    if ![1, 2, 4, 8].contains(&claims.status_list.bits) {
+        return Err(SdjwtVerifierError::StatusListTokenInvalid(
+            "bits must be 1, 2, 4 or 8".to_string(),
+        ));
+    }
+    let key = ((app_addr, route_id), iss, claims.sub.as_str());
+    if let Some(stored) = STATUS_LISTS.may_load(storage, key)? {
+        if stored.iat >= claims.iat {
+            return Err(SdjwtVerifierError::StatusListOutdated);
+        }
+    }
+
+    let compressed = from_base64url(&claims.status_list.lst)?;
+    let lst = decompress_to_vec_zlib_with_limit(&compressed, MAX_STATUS_LIST_LEN)
+        .map_err(|e| SdjwtVerifierError::StatusListTokenInvalid(format!("lst: {e:?}")))?;
+
+    STATUS_LISTS.save(
+        storage,
+        key,
+        &StatusList {
+            bits: claims.status_list.bits,
+            lst: lst.into(),
+            iat: claims.iat,
+            exp: claims.exp,
+        },
+    )?;
+
+    Ok(Event::new("status_list_updated")
+        .add_attribute("iss", iss)
+        .add_attribute("uri", claims.sub)
+        .add_attribute("iat", claims.iat.to_string()))
+}
+
+/// Checks the credential `status` claim against the status list of its issuer stored for the route
+pub fn check_status(
+    storage: &dyn Storage,
+    block_info: &BlockInfo,
+    app_addr: &str,
+    route_id: RouteId,
+    issuer: &str,
+    status: &Value,
+    criterion: &StatusListCriterion,
+) -> Result<(), SdjwtVerifierResultError> {
+    let StatusClaim {
+        status_list: StatusListReference { idx, uri },
+    } = serde_json::from_value(status.clone())
+        .map_err(|e| SdjwtVerifierResultError::StatusClaimInvalid(e.to_string()))?;
+
+    let list = STATUS_LISTS
+        .may_load(storage, ((app_addr, route_id), issuer, &uri))?
+        .ok_or_else(|| SdjwtVerifierResultError::StatusListNotFound(uri.clone()))?;
+    if list.exp.is_some_and(|exp| exp <= block_info.time.seconds()) {
+        return Err(SdjwtVerifierResultError::StatusListExpired(uri));
+    }
+
+    match status_at(&list, idx).ok_or(SdjwtVerifierResultError::StatusIdxOutOfRange(idx))? {
+        STATUS_VALID => Ok(()),
+        status if criterion.accepted_statuses.contains(&status) => Ok(()),
+        STATUS_INVALID => Err(SdjwtVerifierResultError::StatusInvalid(idx)),
+        STATUS_SUSPENDED => Err(SdjwtVerifierResultError::StatusSuspended(idx)),
+        status => Err(SdjwtVerifierResultError::StatusNotAccepted(idx, status)),
+    }
+}
+
+pub fn query_status_list(
+    deps: Deps,
+    app_addr: String,
+    route_id: RouteId,
+    iss: String,
+    uri: String,
+) -> Result<StatusList, SdjwtVerifierError> {
+    STATUS_LISTS
+        .may_load(deps.storage, ((&app_addr, route_id), &iss, &uri))?
+        .ok_or(SdjwtVerifierError::SdjwtVerifierResultError(
+            SdjwtVerifierResultError::StatusListNotFound(uri),
+        ))
+}
+
+/// Removes the status lists stored for the route
+pub fn clear_status_lists(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+) -> StdResult<()> {
+    let lists = STATUS_LISTS
+        .sub_prefix((app_addr, route_id))
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (iss, uri) in lists {
+        STATUS_LISTS.remove(storage, ((app_addr, route_id), &iss, &uri));
+    }
+    Ok(())
+}
+
+/// The status at `idx`, statuses are packed from the least significant bit of each byte
+fn status_at(list: &StatusList, idx: u64) -> Option<u8> {
+    let bits = u64::from(list.bits);
+    let position = idx.checked_mul(bits)?;
+    let byte = list.lst.get(usize::try_from(position / 8).ok()?)?;
+    let mask = ((1u16 << bits) - 1) as u8;
+    Some((byte >> (position % 8)) & mask)
+}
@@ -1,7 +1,17 @@
 use super::errors::{SdjwtVerifierError, SdjwtVerifierResultError};
-use avida_common::types::RegisterRouteRequest;
+use crate::{
+    revocation::{check_not_in_revocation_root, is_credential_revoked, is_revoked_in_shared_list},
+    state::SHARED_REVOCATION_LISTS,
+    status_list::check_status,
+};
+use avida_cheqd::{
+    did_url::{CheqdNetwork, DidResourceUrl},
+    ibc::ResourceError,
+    types::{LinkedResourceMetadata, ResourceReqPacket, ResourceRequirements},
+};
+use avida_common::types::{RegisterRouteRequest, RouteId};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{from_json, Binary, BlockInfo, SubMsg};
+use cosmwasm_std::{from_json, Addr, Binary, BlockInfo, Storage, Timestamp};
 use cw_utils::Expiration;
 use jsonwebtoken::jwk::Jwk;
 use serde::{Deserialize, Serialize};
@@ -16,17 +26,61 @@ pub const IDX: &str = "idx";
 // This is the key to be used in claims that specifies the issuer of the JWT
 pub const ISS_KEY: &str = "iss";
 
+/// This is the key to be used in Criterion::StatusList, the claim value is
+/// `{ "status_list": { "idx": <u64>, "uri": <string> } }` as per the IETF Token Status List
+pub const STATUS_KEY: &str = "status";
+
 #[cw_serde]
 pub struct JwkInfo {
     pub jwk: Binary,
     pub issuer: String,
 }
 
+/// An entry of the contract-wide trusted issuer registry
+#[cw_serde]
+pub struct TrustedIssuer {
+    /// The `iss` value in the credentials
+    pub issuer: String,
+    /// The serialised issuer JWK
+    pub jwk: Binary,
+    /// Any issuer metadata the registry keeps alongside the key
+    pub metadata: Option<Binary>,
+}
+
+/// A contract-wide suspension of an issuer or issuer key
+#[cw_serde]
+pub struct IssuerSuspension {
+    pub reason: Option<String>,
+    pub suspended_at: Timestamp,
+}
+
+/// A compromised issuer key, credentials signed with it are accepted only if issued before `cutoff`
+#[cw_serde]
+pub struct KeyCompromise {
+    /// Credentials must have an `iat` strictly before the cutoff
+    pub cutoff: Timestamp,
+    /// If set, only these credential `idx` are still accepted
+    pub allowed_idx: Option<Vec<u64>>,
+}
+
 #[cw_serde]
 pub struct VerifyResult {
     pub success: bool,
     pub value: Option<Binary>,
     pub error: Option<String>,
+    /// The effective issuer of the verified presentation,
+    /// this is the `iss` claim, or the leaf certificate subject if verified with `x5c`.
+    /// The `iss` claim of `x5c` credentials is not bound to the leaf subject: both must not be
+    /// suspended, and the revocation and status list checks use the `iss` claim
+    pub issuer: Option<String>,
+}
+
+/// The result of a successful `_verify`
+pub struct VerifiedPresentation {
+    /// The effective issuer, see `VerifyResult.issuer`
+    pub issuer: String,
+    /// The verified claims of the presentation
+    pub claims: serde_json::Value,
 }
 
 #[cw_serde]
@@ -40,6 +94,327 @@ pub struct InitRegistration {
 pub struct PendingRoute {
     pub route_id: u64,
     pub app_addr: String,
+    /// What the requested resource is used for
+    #[serde(default)]
+    pub resource: PendingResource,
+    /// The route key source resolved by the request, if it is not the requested resource
+    /// (a newer version of the source resource)
+    pub source: Option<ResourceReqPacket>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum PendingResource {
+    /// The issuer keys of the route
+    #[default]
+    IssuerKeys,
+    /// A Token Status List signed by an issuer of the route
+    StatusList,
+}
+
+/// A resource fetch over IBC that timed out or was acknowledged with an error
+#[cw_serde]
+pub struct FetchFailure {
+    pub resource_req: ResourceReqPacket,
+    pub resource: PendingResource,
+    /// The route key source resolved by the request, if it is not the requested resource
+    pub source: Option<ResourceReqPacket>,
+    pub reason: String,
+    pub failed_at: Timestamp,
+    /// The number of failed attempts
+    pub attempts: u32,
+    /// When the fetch can be retried with `RetryPendingFetches`,
+    /// `None` while a retry is in flight or once the attempts are exhausted
+    pub next_retry: Option<Timestamp>,
+}
+
+/// A cheqd resource the issuer keys of a route are fetched from,
+/// the `data_or_location` of a `TrustRegistry::Cheqd` issuer source.
+/// It can also be given as a DID-Linked Resource URL, see `CheqdKeySource::parse`
+#[cw_serde]
+#[serde(rename_all = "camelCase")]
+pub struct CheqdKeySource {
+    pub resource_id: String,
+    pub collection_id: String,
+    /// The network of the resource DID, checked against the network of the verifier
+    #[serde(default)]
+    pub network: Option<CheqdNetwork>,
+    /// The format of the resource data the keys are taken from
+    #[serde(default)]
+    pub format: KeyResourceFormat,
+    /// The accepted versions of the resource
+    #[serde(default)]
+    pub version: ResourceVersion,
+    /// The required `media_type` of the resource
+    #[serde(default)]
+    pub media_type: Option<String>,
+    /// The required `resource_type` of the resource
+    #[serde(default)]
+    pub resource_type: Option<String>,
+    /// The required `resource_name` of the resource
+    #[serde(default)]
+    pub resource_name: Option<String>,
+    /// The maximum age of the resource, from its `created` time
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+    /// The time the resource must have been created after
+    #[serde(default)]
+    pub created_after: Option<Timestamp>,
+}
+
+impl CheqdKeySource {
+    /// Parses a cheqd issuer source, either a JSON `CheqdKeySource`
+    /// or a DID-Linked Resource URL, as a JSON string or as is
+    pub fn parse(data: &Binary) -> Result<Self, SdjwtVerifierError> {
+        let url = match data.first() {
+            Some(b'{') => return Ok(from_json(data)?),
+            Some(b'"') => from_json::<String>(data)?,
+            _ => String::from_utf8(data.to_vec())
+                .map_err(|_| SdjwtVerifierError::DataDeserialization)?,
+        };
+        Ok(Self::from_url(&url.parse()?)?)
+    }
+
+    /// The key source of a DID-Linked Resource URL, which must have a resource id.
+    /// The name, type and version of the URL query are required from the resource
+    pub fn from_url(url: &DidResourceUrl) -> Result<Self, ResourceError> {
+        let ResourceReqPacket {
+            resource_id,
+            collection_id,
+        } = url.resource_req()?;
+        Ok(CheqdKeySource {
+            resource_id,
+            collection_id,
+            network: Some(url.network),
+            format: KeyResourceFormat::default(),
+            version: url
+                .resource_version
+                .clone()
+                .map(ResourceVersion::Version)
+                .unwrap_or_default(),
+            media_type: None,
+            resource_type: url.resource_type.clone(),
+            resource_name: url.resource_name.clone(),
+            max_age_days: None,
+            created_after: None,
+        })
+    }
+
+    pub fn resource_req(&self) -> ResourceReqPacket {
+        ResourceReqPacket {
+            resource_id: self.resource_id.clone(),
+            collection_id: self.collection_id.clone(),
+        }
+    }
+
+    pub fn requirements(&self) -> ResourceRequirements {
+        ResourceRequirements {
+            media_type: self.media_type.clone(),
+            resource_type: self.resource_type.clone(),
+            resource_name: self.resource_name.clone(),
+        }
+    }
+
+    pub fn freshness(&self) -> ResourceFreshness {
+        ResourceFreshness {
+            max_age_days: self.max_age_days,
+            created_after: self.created_after,
+        }
+    }
+
+    /// The route key source of this resource, requested at `requested_at`
+    pub fn route_key_source(&self, requested_at: Timestamp) -> RouteKeySource {
+        RouteKeySource {
+            resource_req: self.resource_req(),
+            version: self.version.clone(),
+            format: self.format.clone(),
+            requirements: self.requirements(),
+            freshness: self.freshness(),
+            requested_at,
+            fetched_at: None,
+        }
+    }
+}
+
+/// The format of a cheqd resource the issuer keys are taken from
+#[cw_serde]
+#[derive(Default)]
+pub enum KeyResourceFormat {
+    /// A map of issuer (`iss`) to its JWK
+    #[default]
+    Avida,
+    /// The DID Document of the DID the resource is linked to, its assertion method keys are
+    /// keyed by the DID and by their verification method id, for `kid` matching
+    DidDocument,
+    /// A JWK Set (`{"keys": [...]}`) of `issuer`, its Ed25519 keys are
+    /// keyed by the issuer and by `<issuer>#<kid>`
+    Jwks { issuer: String },
+    /// A single JWK of `issuer`
+    Jwk { issuer: String },
+    /// SD-JWT VC issuer metadata (`/.well-known/jwt-vc-issuer`) with inline `jwks`,
+    /// keyed like a JWK Set of its `issuer`
+    JwtVcIssuer,
+}
+
+/// The issuer keys of a source, by issuer and by key id
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IssuerKeys {
+    /// The key of each issuer `iss`, used when the credential `kid` selects no other key
+    pub issuers: HashMap<String, Jwk>,
+    /// The keys with a `kid`, by key id (`iss#kid`)
+    pub key_ids: HashMap<String, Jwk>,
+}
+
+/// Maximum `max_age_days` of a cheqd key source, about a century
+pub const MAX_RESOURCE_AGE_DAYS: u64 = 36_525;
+
+/// How recently a cheqd resource must have been created to be accepted
+#[cw_serde]
+#[derive(Default)]
+pub struct ResourceFreshness {
+    pub max_age_days: Option<u64>,
+    pub created_after: Option<Timestamp>,
+}
+
+impl ResourceFreshness {
+    /// Whether a resource created at `created` is still fresh at `now`,
+    /// a maximum age past the largest timestamp never expires
+    pub fn is_fresh(&self, created: Timestamp, now: Timestamp) -> bool {
+        self.max_age_days.is_none_or(|days| {
+            days.checked_mul(86_400 * 1_000_000_000)
+                .and_then(|age| created.nanos().checked_add(age))
+                .is_none_or(|expires| expires >= now.nanos())
+        }) && self
+            .created_after
+            .is_none_or(|created_after| created > created_after)
+    }
+}
+
+/// The version of a cheqd resource the route keys are taken from
+#[cw_serde]
+#[derive(Default)]
+pub enum ResourceVersion {
+    /// The requested resource
+    #[default]
+    Requested,
+    /// The requested resource, which must have this `resource_version`
+    Version(String),
+    /// The requested resource, which must have this checksum (hex encoded SHA-256 of the data)
+    Checksum(String),
+    /// The latest version of the requested resource, following its `next_version_id`
+    Latest,
+}
+
+/// A cheqd resource the issuer keys of a route are fetched from
+#[cw_serde]
+pub struct RouteKeySource {
+    pub resource_req: ResourceReqPacket,
+    #[serde(default)]
+    pub version: ResourceVersion,
+    #[serde(default)]
+    pub format: KeyResourceFormat,
+    #[serde(default)]
+    pub requirements: ResourceRequirements,
+    #[serde(default)]
+    pub freshness: ResourceFreshness,
+    /// When the resource was last requested
+    pub requested_at: Timestamp,
+    /// When the resource was last fetched, `None` until it is first acknowledged
+    pub fetched_at: Option<Timestamp>,
+}
+
+/// The cheqd resource version the keys of a route source were taken from
+#[cw_serde]
+pub struct ResolvedKeyVersion {
+    pub source: ResourceReqPacket,
+    pub metadata: LinkedResourceMetadata,
+}
+
+/// Whether the issuer keys of a route can be used
+#[cw_serde]
+pub enum RouteStatus {
+    /// Some issuer keys are still being fetched
+    PendingFetch,
+    /// All the issuer keys are available
+    Ready,
+    /// Fetching some issuer keys failed, see the route fetch failures
+    FetchFailed,
+    /// The issuer keys are available, but a later fetch of a source is pending or failed,
+    /// or a source resource is no longer fresh enough
+    Stale,
+}
+
+#[cw_serde]
+pub struct RouteStatusResponse {
+    pub status: RouteStatus,
+    /// The cheqd resources the route keys are fetched from
+    pub sources: Vec<RouteKeySource>,
+    pub failures: Vec<FetchFailure>,
+}
+
+/// An IBC channel to cheqd connected to the contract
+#[cw_serde]
+pub struct CheqdChannel {
+    pub channel_id: String,
+    pub connection_id: String,
+    pub counterparty_channel_id: String,
+    /// When the channel was closed, it can no longer be used
+    pub closed_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct CheqdChannelsResponse {
+    /// The channel the resource requests are sent on, if any
+    pub active: Option<String>,
+    pub channels: Vec<CheqdChannel>,
+}
+
+/// A verified Token Status List, stored by route, issuer and list `uri` (the token `sub`)
+#[cw_serde]
+pub struct StatusList {
+    /// The number of bits per status, 1, 2, 4 or 8
+    pub bits: u8,
+    /// The decompressed status list
+    pub lst: Binary,
+    /// The issuance time of the list token, only newer lists replace the stored one
+    pub iat: u64,
+    /// The expiry time of the list token, if any
+    pub exp: Option<u64>,
+}
+
+/// A committed root of the route revocations
+#[cw_serde]
+pub struct RevocationRoot {
+    /// The sparse Merkle tree root, 32 bytes
+    pub root: Binary,
+    pub committed_at: Timestamp,
+}
+
+/// A named revocation list, referenced by the routes of any app with `Criterion::NotInRevocationList`
+#[cw_serde]
+pub struct SharedRevocationList {
+    pub owner: Addr,
+    /// Addresses allowed to update the list besides the owner
+    pub editors: Vec<Addr>,
+}
+
+/// An address allowed to update the route revocations of the credentials of an issuer
+#[cw_serde]
+pub struct RevocationDelegate {
+    pub delegate: String,
+    pub iss: String,
+}
+
+/// Proof that a credential `idx` is not a revoked leaf of the sparse Merkle tree,
+/// as submitted with `revocation_proof` on verification.
+///
+/// The tree has a leaf for every `u64` idx, empty leaves are 32 zero bytes and
+/// a node is `sha256(left || right)`, with the bits of `idx` from the least significant
+/// selecting the right child (1) or the left child (0) from the leaf up
+#[cw_serde]
+pub struct SmtNonMembershipProof {
+    /// The 64 siblings from the leaf to the root, `None` for an empty subtree
+    pub siblings: Vec<Option<Binary>>,
 }
 
 /// This is an internal struct that is used to organised the input RegisterRouteRequest to handle
@@ -47,18 +422,19 @@ pub struct PendingRoute {
 /// 2. if issuer data is directly provided, it will validate the jwk
 pub(crate) struct _RegistrationRequest {
     pub verification_requirements: VerificationRequirements,
-    pub ibc_msgs: Option<Vec<SubMsg>>,
+    /// The resources to request once the route is saved
+    pub resource_requests: Vec<(ResourceReqPacket, PendingRoute)>,
 }
 
 impl _RegistrationRequest {
     /// Create a new verification request
     pub fn new(
         verification_requirements: VerificationRequirements,
-        ibc_msgs: Option<Vec<SubMsg>>,
+        resource_requests: Vec<(ResourceReqPacket, PendingRoute)>,
     ) -> Self {
         _RegistrationRequest {
             verification_requirements,
-            ibc_msgs,
+            resource_requests,
         }
     }
 }
@@ -91,6 +467,15 @@ pub struct VerificationRequirements {
     /// Usig this type as it is ser/deserializable
     // use the `iss` value here as the key of the map
     pub issuer_pubkeys: Option<HashMap<String, Jwk>>,
+    /// The issuer keys selected by a `kid`, by key id (`iss#kid`)
+    #[serde(default)]
+    pub issuer_key_ids: HashMap<String, Jwk>,
+    /// DER encoded root CA certificates that anchor the `x5c` header chain
+    #[serde(default)]
+    pub trusted_roots: Vec<Binary>,
+    /// Names of the contract-wide trusted issuer registry entries this route trusts
+    #[serde(default)]
+    pub registry_issuers: Vec<String>,
 }
 
 impl VerificationRequirements {
@@ -104,6 +489,9 @@ impl VerificationRequirements {
                 None => vec![],
             },
             issuer_pubkeys,
+            issuer_key_ids: HashMap::new(),
+            trusted_roots: vec![],
+            registry_issuers: vec![],
         })
     }
 }
@@ -120,6 +508,23 @@ pub enum Criterion {
     /// this can be used in any form,
     /// but it is designed to be used with key IDX as a revocationlist
     NotContainedIn(Vec<u64>),
+    /// Checks the credential status in the stored Token Status List of its issuer,
+    /// this must be used with the key STATUS_KEY
+    StatusList(StatusListCriterion),
+    /// Requires a proof that the credential is not revoked in the committed revocation root
+    /// of the route, this must be used with the key IDX
+    NotInRevocationRoot,
+    /// Checks the credential is not revoked in the shared revocation list with this id,
+    /// this must be used with the key IDX
+    NotInRevocationList(String),
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct StatusListCriterion {
+    /// Statuses accepted in addition to `VALID` (0x00),
+    /// e.g. `SUSPENDED` (0x02) or an application specific status
+    pub accepted_statuses: Vec<u8>,
 }
 
 #[cw_serde]
@@ -135,12 +540,34 @@ pub enum MathsOperator {
     EqualTo,
 }
 
-/// Validate the verified claims against the presentation request
+/// The context the verified claims are validated in
+pub struct ValidationContext<'a> {
+    pub block_info: &'a BlockInfo,
+    pub storage: &'a dyn Storage,
+    /// The `iss` of the credential
+    pub issuer: &'a str,
+    /// The route `idx` revocations are checked against
+    pub app_addr: &'a str,
+    pub route_id: RouteId,
+    /// The holder supplied proof for `Criterion::NotInRevocationRoot`
+    pub revocation_proof: Option<&'a SmtNonMembershipProof>,
+}
+
+/// Validate the verified claims of the credential against the presentation request
 pub fn validate(
     presentation_request: PresentationReq,
     verified_claims: serde_json::Value,
-    block_info: &BlockInfo,
+    ctx: &ValidationContext,
 ) -> Result<(), SdjwtVerifierResultError> {
+    let ValidationContext {
+        block_info,
+        storage,
+        issuer,
+        app_addr,
+        route_id,
+        revocation_proof,
+    } = *ctx;
+
     match verified_claims {
         serde_json::Value::Null if presentation_request.is_empty() => Ok(()),
         serde_json::Value::Null => Err(SdjwtVerifierResultError::DisclosedClaimNotFound(
@@ -180,10 +607,50 @@ pub fn validate(
                         let idx_u64 = idx
                             .as_u64()
                             .ok_or(SdjwtVerifierResultError::CriterionValueNumberInvalid)?;
-                        if revocation_list.contains(&idx_u64) {
+                        // The route revocations are stored outside of the requirements
+                        if revocation_list.contains(&idx_u64)
+                            || (attribute == IDX
+                                && is_credential_revoked(
+                                    storage, app_addr, route_id, issuer, idx_u64,
+                                )?)
+                        {
                             return Err(SdjwtVerifierResultError::IdxRevoked(idx_u64));
                         }
                     }
+                    (Criterion::NotInRevocationRoot, Some(serde_json::Value::Number(idx)))
+                        if attribute == IDX =>
+                    {
+                        let idx = idx
+                            .as_u64()
+                            .ok_or(SdjwtVerifierResultError::CriterionValueNumberInvalid)?;
+                        check_not_in_revocation_root(
+                            storage,
+                            block_info,
+                            app_addr,
+                            route_id,
+                            idx,
+                            revocation_proof,
+                        )?;
+                    }
+                    (
+                        Criterion::NotInRevocationList(list_id),
+                        Some(serde_json::Value::Number(idx)),
+                    ) if attribute == IDX => {
+                        let idx = idx
+                            .as_u64()
+                            .ok_or(SdjwtVerifierResultError::CriterionValueNumberInvalid)?;
+                        if !SHARED_REVOCATION_LISTS.has(storage, list_id) {
+                            return Err(SdjwtVerifierResultError::SharedRevocationListNotFound(
+                                list_id.clone(),
+                            ));
+                        }
+                        if is_revoked_in_shared_list(storage, list_id, idx)? {
+                            return Err(SdjwtVerifierResultError::IdxRevoked(idx));
+                        }
+                    }
+                    (Criterion::StatusList(c), Some(status)) if attribute == STATUS_KEY => {
+                        check_status(storage, block_info, app_addr, route_id, issuer, status, c)?;
+                    }
                     (Criterion::String(c_val), Some(serde_json::Value::String(p_val))) => {
                         if p_val != c_val {
                             return Err(SdjwtVerifierResultError::CriterionValueFailed(attribute));
@@ -1,31 +1,56 @@
 use crate::{
+    channel::{active_channel, is_active_channel},
     errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    fetch::{
+        check_key_freshness, check_key_version, clear_fetch_failure, clear_fetch_failures,
+        clear_key_sources, clear_key_versions, merge_source_keys, pending_key_source,
+        record_fetch_failure, save_key_source, save_key_version, set_key_source_fetched,
+        stale_key_source, INLINE_KEYS_SOURCE,
+    },
+    issuer_keys::{issuer_key_id, resource_issuer_keys},
+    registry::{
+        ensure_issuer_not_suspended, ensure_key_not_compromised, jwk_thumbprint,
+        registry_issuer_pubkey,
+    },
+    revocation::{
+        _update_revocation_root, clear_revocation_roots, clear_revocations,
+        ensure_shared_revocation_lists_exist, remove_revocation_delegate, replace_revocations,
+        restore_inline_revocations, revocation_delegate_issuer, revocation_updates,
+        set_revocation_delegate, take_inline_revocations, update_revocations,
+        validate_revocation_updates,
+    },
     state::*,
+    status_list::{_update_status_list, clear_status_lists},
     types::{
-        validate, Criterion, JwkInfo, PendingRoute, PresentationReq, VerificationRequirements,
-        VerifyResult, _RegistrationRequest, IDX, ISS_KEY,
+        validate, CheqdKeySource, Criterion, IssuerKeys, JwkInfo, PendingResource, PendingRoute,
+        PresentationReq, ResourceVersion, RouteKeySource, SmtNonMembershipProof, ValidationContext,
+        VerificationRequirements, VerifiedPresentation, VerifyResult, _RegistrationRequest, IDX,
+        ISS_KEY, MAX_RESOURCE_AGE_DAYS,
     },
+    x509::{validate_root_certificate, verify_x5c_chain},
 };
 use avida_cheqd::{
     ibc::{get_timeout_timestamp, ibc_packet_ack_resource_extractor, HOUR_PACKET_LIFETIME},
-    types::ResourceReqPacket,
+    types::{ResourceReqPacket, ResourceRequirements, ResourceWithMetadata},
 };
 use avida_common::types::{
-    RegisterRouteRequest, RouteId, RouteVerificationRequirements, TrustRegistry,
-    VerfiablePresentation, MAX_PRESENTATION_LENGTH,
+    IssuerSourceOrData, RegisterRouteRequest, RouteId, RouteVerificationRequirements,
+    TrustRegistry, VerfiablePresentation, MAX_PRESENTATION_LENGTH,
 };
 use cosmwasm_std::Order;
 use cosmwasm_std::{
-    ensure, from_json, to_json_binary, Addr, Binary, BlockInfo, CosmosMsg, Deps, DepsMut, Env,
-    IbcBasicResponse, IbcChannelConnectMsg, IbcPacketAckMsg, IbcTimeout, MessageInfo, Response,
-    Storage, SubMsg,
+    ensure, from_json, to_json_binary, Addr, Binary, BlockInfo, CosmosMsg, Deps, DepsMut, Empty,
+    Env, IbcBasicResponse, IbcPacketAckMsg, IbcPacketTimeoutMsg, IbcTimeout, MessageInfo, Response,
+    StdResult, Storage, SubMsg,
 };
+use cw_storage_plus::Bound;
 use sd_jwt_rs::{SDJWTSerializationFormat, SDJWTVerifier};
 use serde_json::Value;
 use std::collections::HashMap;
 
-use avida_common::types::UpdateRevocationListRequest;
+use avida_common::types::{IdxRange, UpdateRevocationListRequest};
 use jsonwebtoken::{
+    decode_header,
     jwk::{AlgorithmParameters, EllipticCurve, Jwk, OctetKeyPairParameters},
     DecodingKey,
 };
@@ -42,9 +67,12 @@ pub fn handle_update_revocation_list(
         route_id,
         revoke,
         unrevoke,
+        revoke_ranges,
+        unrevoke_ranges,
+        iss,
     } = request;
 
-    let mut route_requirements =
+    let route_requirements =
         APP_ROUTES_REQUIREMENTS.load(deps.storage, (app_addr.clone(), route_id))?;
 
     let valid_app_addr = deps.api.addr_validate(&app_addr)?;
@@ -53,37 +81,130 @@ pub fn handle_update_revocation_list(
         .load(deps.storage, valid_app_addr.as_str())
         .map_err(|_| SdjwtVerifierError::AppIsNotRegistered)?;
 
-    if app_admin != info.sender {
-        return Err(SdjwtVerifierError::UnauthorisedCaller);
-    }
+    // Revocation delegates can only update the credentials of their issuer
+    let iss = if app_admin == info.sender {
+        iss
+    } else {
+        let delegated =
+            revocation_delegate_issuer(deps.storage, &app_addr, route_id, &info.sender)?
+                .ok_or(SdjwtVerifierError::UnauthorisedCaller)?;
+        if iss.is_some_and(|iss| iss != delegated) {
+            return Err(SdjwtVerifierError::UnauthorisedCaller);
+        }
+        Some(delegated)
+    };
 
-    route_requirements
+    // The route must check the `idx` against its revocation list
+    let req = route_requirements
         .presentation_required
-        .iter_mut()
+        .iter()
         .find(|req| req.attribute == IDX)
-        .map(|req| -> Result<_, SdjwtVerifierError> {
-            if let Criterion::NotContainedIn(revocation_list) = &mut req.criterion {
-                for r in revoke {
-                    if !revocation_list.contains(&r) {
-                        revocation_list.push(r);
-                    }
-                }
+        .ok_or(SdjwtVerifierError::IDXNotInRequirement)?;
+    if !matches!(req.criterion, Criterion::NotContainedIn(_)) {
+        return Err(SdjwtVerifierError::RevocationListType);
+    }
 
-                for r in unrevoke {
-                    revocation_list.retain(|&x| x != r);
-                }
-                Ok(())
-            } else {
-                Err(SdjwtVerifierError::RevocationListType)
-            }
-        })
-        .ok_or(SdjwtVerifierError::IDXNotInRequirement)??;
+    let updates = revocation_updates(revoke, unrevoke, revoke_ranges, unrevoke_ranges);
+    validate_revocation_updates(&updates)?;
 
-    APP_ROUTES_REQUIREMENTS.save(deps.storage, (app_addr, route_id), &route_requirements)?;
+    update_revocations(deps.storage, &app_addr, route_id, iss.as_deref(), &updates)?;
 
     Ok(Response::default())
 }
 
+pub fn handle_set_revocation_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    app_addr: String,
+    route_id: RouteId,
+    delegate: String,
+    iss: String,
+) -> Result<Response, SdjwtVerifierError> {
+    let delegate = deps.api.addr_validate(&delegate)?;
+    ensure_route_admin(deps.storage, &info, &app_addr, route_id)?;
+
+    set_revocation_delegate(deps.storage, &app_addr, route_id, &delegate, &iss)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_revocation_delegate")
+        .add_attribute("route_id", route_id.to_string())
+        .add_attribute("delegate", delegate)
+        .add_attribute("iss", iss))
+}
+
+pub fn handle_remove_revocation_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    app_addr: String,
+    route_id: RouteId,
+    delegate: String,
+) -> Result<Response, SdjwtVerifierError> {
+    let delegate = deps.api.addr_validate(&delegate)?;
+    ensure_route_admin(deps.storage, &info, &app_addr, route_id)?;
+
+    remove_revocation_delegate(deps.storage, &app_addr, route_id, &delegate);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_revocation_delegate")
+        .add_attribute("route_id", route_id.to_string())
+        .add_attribute("delegate", delegate))
+}
+
+/// Ensures the sender is the app admin and the route is registered
+fn ensure_route_admin(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+    app_addr: &str,
+    route_id: RouteId,
+) -> Result<(), SdjwtVerifierError> {
+    let app_admin = APP_ADMINS
+        .load(storage, app_addr)
+        .map_err(|_| SdjwtVerifierError::AppIsNotRegistered)?;
+    if app_admin != info.sender {
+        return Err(SdjwtVerifierError::UnauthorisedCaller);
+    }
+    if !APP_ROUTES_REQUIREMENTS.has(storage, (app_addr.to_owned(), route_id)) {
+        return Err(SdjwtVerifierError::RouteNotRegistered);
+    }
+    Ok(())
+}
+
+pub fn handle_update_revocation_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    app_addr: String,
+    route_id: RouteId,
+    root: Binary,
+) -> Result<Response, SdjwtVerifierError> {
+    let app_admin = APP_ADMINS
+        .load(deps.storage, &app_addr)
+        .map_err(|_| SdjwtVerifierError::AppIsNotRegistered)?;
+    if app_admin != info.sender {
+        return Err(SdjwtVerifierError::UnauthorisedCaller);
+    }
+
+    _update_route_revocation_root(deps.storage, &env, app_addr, route_id, root)
+}
+
+/// Commits a new revocation root for a registered route
+pub fn _update_route_revocation_root(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: String,
+    route_id: RouteId,
+    root: Binary,
+) -> Result<Response, SdjwtVerifierError> {
+    if !APP_ROUTES_REQUIREMENTS.has(storage, (app_addr.clone(), route_id)) {
+        return Err(SdjwtVerifierError::RouteNotRegistered);
+    }
+
+    let event = _update_revocation_root(storage, env, &app_addr, route_id, root)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_revocation_root")
+        .add_event(event))
+}
+
 pub fn handle_register(
     deps: DepsMut,
     env: Env,
@@ -105,14 +226,12 @@ pub fn handle_register(
 pub fn handle_verify(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    presentation: VerfiablePresentation,
+    app_addr: String,
     route_id: RouteId,
-    app_addr: Option<String>,
+    presentation: VerfiablePresentation,
     additional_requirements: Option<Binary>,
+    revocation_proof: Option<Binary>,
 ) -> Result<Response, SdjwtVerifierError> {
-    let app_addr = app_addr.unwrap_or_else(|| info.sender.to_string());
-
     let res = _handle_verify(
         deps,
         env,
@@ -120,6 +239,7 @@ pub fn handle_verify(
         route_id,
         presentation,
         additional_requirements,
+        revocation_proof,
     )?;
 
     Ok(Response::new().set_data(to_json_binary(&res)?))
@@ -133,6 +253,7 @@ pub fn handle_sudo_verify(
     route_id: RouteId,
     presentation: VerfiablePresentation,
     additional_requirements: Option<Binary>,
+    revocation_proof: Option<Binary>,
 ) -> Result<Response, SdjwtVerifierError> {
     let res = _handle_verify(
         deps,
@@ -141,12 +262,14 @@ pub fn handle_sudo_verify(
         route_id,
         presentation,
         additional_requirements,
+        revocation_proof,
     )?;
 
     if res.success {
         Ok(Response::new()
             .add_attribute("action", "sudo_verify")
             .add_attribute("success", "true")
+            .add_attribute("issuer", res.issuer.unwrap_or_default())
             .set_data(res.value.unwrap_or_default()))
     } else {
         Err(SdjwtVerifierError::VerifyResultError(res.error.unwrap()))
@@ -160,34 +283,47 @@ pub fn _handle_verify(
     route_id: RouteId,
     presentation: VerfiablePresentation,
     additional_requirements: Option<Binary>,
+    revocation_proof: Option<Binary>,
 ) -> Result<VerifyResult, SdjwtVerifierError> {
     let additional_requirements: Option<PresentationReq> =
         additional_requirements.map(from_json).transpose()?;
+    let revocation_proof: Option<SmtNonMembershipProof> =
+        revocation_proof.map(from_json).transpose()?;
 
-    let requirements = APP_ROUTES_REQUIREMENTS
-        .load(deps.storage, (app_addr, route_id))
+    let mut requirements = APP_ROUTES_REQUIREMENTS
+        .load(deps.storage, (app_addr.clone(), route_id))
         .map_err(|_| SdjwtVerifierError::RouteNotRegistered)?;
 
+    if let Some(additional_requirements) = additional_requirements {
+        requirements
+            .presentation_required
+            .extend(additional_requirements);
+    }
+
     let max_len = MAX_PRESENTATION_LENGTH.load(deps.storage)?;
 
     let res = _verify(
+        deps.as_ref(),
+        (&app_addr, route_id),
         presentation,
         requirements,
         max_len,
         &env.block,
-        additional_requirements,
+        revocation_proof.as_ref(),
     );
 
     let verify_result = match res {
-        Ok(value) => VerifyResult {
+        Ok(verified) => VerifyResult {
             success: true,
-            value: Some(to_json_binary(&value)?),
+            value: Some(to_json_binary(&verified.claims)?),
             error: None,
+            issuer: Some(verified.issuer),
         },
         Err(error) => VerifyResult {
             success: false,
             value: None,
             error: Some(error.to_string()),
+            issuer: None,
         },
     };
     Ok(verify_result)
@@ -222,6 +358,7 @@ pub fn handle_update(
 
 pub fn handle_deregister(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     app_addr: String,
 ) -> Result<Response, SdjwtVerifierError> {
@@ -241,8 +378,76 @@ pub fn handle_deregister(
         return Err(SdjwtVerifierError::UnauthorisedCaller);
     }
 
-    _deregister(deps.storage, app_addr.as_str())
+    _deregister(deps.storage, &env, app_addr.as_str())
+}
+pub fn handle_update_status_list(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    app_addr: String,
+    route_id: RouteId,
+    status_list: IssuerSourceOrData,
+) -> Result<Response, SdjwtVerifierError> {
+    // Directly provided lists are signed by the issuer, fetching them is up to the app admin
+    if status_list.source.is_some() {
+        let app_admin = APP_ADMINS
+            .load(deps.storage, &app_addr)
+            .map_err(|_| SdjwtVerifierError::AppIsNotRegistered)?;
+        if app_admin != info.sender {
+            return Err(SdjwtVerifierError::UnauthorisedCaller);
+        }
+    }
+
+    _update_route_status_list(deps.storage, &env, app_addr, route_id, status_list)
+}
+
+/// Stores a directly provided status list, or requests it from the trust registry
+pub fn _update_route_status_list(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: String,
+    route_id: RouteId,
+    status_list: IssuerSourceOrData,
+) -> Result<Response, SdjwtVerifierError> {
+    let requirements = APP_ROUTES_REQUIREMENTS
+        .load(storage, (app_addr.clone(), route_id))
+        .map_err(|_| SdjwtVerifierError::RouteNotRegistered)?;
+
+    match status_list.source {
+        None => {
+            let event = _update_status_list(
+                storage,
+                &env.block,
+                &app_addr,
+                route_id,
+                &requirements,
+                &status_list.data_or_location,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "update_status_list")
+                .add_event(event))
+        }
+        Some(TrustRegistry::Cheqd) => {
+            let resource_req_packet: ResourceReqPacket = from_json(&status_list.data_or_location)?;
+            let res = make_resource_request(
+                storage,
+                env,
+                resource_req_packet,
+                PendingRoute {
+                    app_addr,
+                    route_id,
+                    resource: PendingResource::StatusList,
+                    source: None,
+                },
+            )?;
+            Ok(res.add_attribute("action", "request_status_list"))
+        }
+        Some(_) => Err(SdjwtVerifierError::StatusListTokenInvalid(
+            "unsupported trust registry".to_string(),
+        )),
+    }
 }
+
 pub fn handle_sudo_update(
     deps: DepsMut,
     env: Env,
@@ -290,31 +495,56 @@ pub fn query_route_requirements(
     app_addr: String,
     route_id: RouteId,
 ) -> Result<VerificationRequirements, SdjwtVerifierError> {
-    let req = APP_ROUTES_REQUIREMENTS
-        .load(deps.storage, (app_addr, route_id))
+    let mut req = APP_ROUTES_REQUIREMENTS
+        .load(deps.storage, (app_addr.clone(), route_id))
         .map_err(|_| SdjwtVerifierError::RouteNotRegistered)?;
+    // The revocations are stored outside of the requirements
+    restore_inline_revocations(deps.storage, &app_addr, route_id, &mut req)?;
     Ok(req)
 }
 
+/// Routes with an issuer key for `iss`, either directly provided or fetched from a trust registry.
+/// Routes trusting the issuer through the contract-wide registry are not indexed,
+/// as the registry entries can be updated independently of the routes
+pub fn query_routes_by_issuer(
+    deps: Deps,
+    iss: String,
+    start_after: Option<(String, RouteId)>,
+    limit: Option<u32>,
+) -> Result<Vec<(String, RouteId)>, SdjwtVerifierError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after
+        .as_ref()
+        .map(|(app_addr, route_id)| Bound::exclusive((app_addr.as_str(), *route_id)));
+
+    Ok(ISSUER_ROUTES
+        .sub_prefix(&iss)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
 /// Verify the provided presentation within the context of the given route
 pub fn _verify(
+    deps: Deps,
+    (app_addr, route_id): (&str, RouteId),
     presentation: VerfiablePresentation,
     requirements: VerificationRequirements,
     max_presentation_len: usize,
     block_info: &BlockInfo,
-    additional_requirements: Option<PresentationReq>,
-) -> Result<Value, SdjwtVerifierResultError> {
+    revocation_proof: Option<&SmtNonMembershipProof>,
+) -> Result<VerifiedPresentation, SdjwtVerifierResultError> {
     // Ensure the presentation is not too large
     ensure!(
         presentation.len() <= max_presentation_len,
         SdjwtVerifierResultError::PresentationTooLarge
     );
 
+    let presentation = String::from_utf8(presentation.to_vec())
+        .map_err(|e| SdjwtVerifierResultError::StringConversion(e.to_string()))?;
+
     let mut common = SDJWTCommon::default();
-    common.parse_compact_sd_jwt(
-        String::from_utf8(presentation.to_vec())
-            .map_err(|e| SdjwtVerifierResultError::StringConversion(e.to_string()))?,
-    )?;
+    common.parse_compact_sd_jwt(presentation.clone())?;
 
     let payload = common
         .unverified_input_sd_jwt_payload
@@ -328,48 +558,140 @@ pub fn _verify(
             "Iss is not a string".to_owned(),
         ))?;
 
-    if let Some(pubkeys) = requirements.issuer_pubkeys {
-        let decoding_key = if let Some(pubkey) = pubkeys.get(iss) {
-            DecodingKey::from_jwk(pubkey)
-                .map_err(|e| SdjwtVerifierResultError::JwtError(e.to_string()))?
-        } else {
-            return Err(SdjwtVerifierResultError::PubKeyNotFound);
-        };
-
-        // We verify the presentation
-        let sdjwt_verifier = SDJWTVerifier::new(
-            String::from_utf8(presentation.to_vec())
-                .map_err(|e| SdjwtVerifierResultError::StringConversion(e.to_string()))?,
-            Box::new(move |_, _| decoding_key.clone()),
-            None, // This version does not support key binding
-            None, // This version does not support key binding
-            SDJWTSerializationFormat::Compact,
-        )
-        .map_err(|e| SdjwtVerifierResultError::SdJwt(e.to_string()))?;
+    // Suspended issuers are rejected for all apps before any route level key lookup
+    ensure_issuer_not_suspended(deps.storage, iss, None)?;
 
-        let combined_requirements = if let Some(additional_requirements) = additional_requirements {
-            let mut combined_requirements = requirements.presentation_required.clone();
-            combined_requirements.extend(additional_requirements);
-            combined_requirements
-        } else {
-            requirements.presentation_required
-        };
-
-        // We validate the verified claims against the requirements
-        validate(
-            combined_requirements,
-            sdjwt_verifier.verified_claims.clone(),
-            block_info,
+    let ctx = ValidationContext {
+        block_info,
+        storage: deps.storage,
+        issuer: iss,
+        app_addr,
+        route_id,
+        revocation_proof,
+    };
+
+    // If the route trusts root CAs and the issuer provides a certificate chain,
+    // the credential is verified with the leaf certificate key
+    let x5c = if requirements.trusted_roots.is_empty() {
+        None
+    } else {
+        // The issuer signed JWT is the first component of the compact presentation
+        let jwt = presentation.split('~').next().unwrap_or_default();
+        decode_header(jwt)
+            .map_err(|e| SdjwtVerifierResultError::JwtError(e.to_string()))?
+            .x5c
+    };
+
+    let (issuer, key_thumbprint, claims) = if let Some(x5c) = x5c {
+        let leaf = verify_x5c_chain(deps.api, &x5c, &requirements.trusted_roots, block_info.time)?;
+        // The leaf subject is the effective issuer, the revocation checks of `ctx` still use `iss`
+        ensure_issuer_not_suspended(deps.storage, &leaf.subject, Some(&leaf.key_thumbprint))?;
+
+        let claims = verify_and_validate(
+            presentation,
+            leaf.decoding_key,
+            &requirements.presentation_required,
+            &ctx,
         )?;
+        (leaf.subject, Some(leaf.key_thumbprint), claims)
+    } else {
+        let kid = decode_header(presentation.split('~').next().unwrap_or_default())
+            .ok()
+            .and_then(|header| header.kid);
+        let pubkey = issuer_pubkey(deps.storage, &requirements, iss, kid.as_deref())?;
+
+        // Keys fetched from cheqd are only used while their resources are fresh enough
+        if let Some(source) =
+            stale_key_source(deps.storage, app_addr, route_id, &pubkey, block_info.time)?
+        {
+            return Err(SdjwtVerifierResultError::IssuerKeysNotFresh(
+                source.to_string(),
+            ));
+        }
+        let key_thumbprint = jwk_thumbprint(&pubkey);
+        ensure_issuer_not_suspended(deps.storage, iss, key_thumbprint.as_deref())?;
 
-        Ok(sdjwt_verifier.verified_claims)
+        let decoding_key = DecodingKey::from_jwk(&pubkey)
+            .map_err(|e| SdjwtVerifierResultError::JwtError(e.to_string()))?;
+
+        let claims = verify_and_validate(
+            presentation,
+            decoding_key,
+            &requirements.presentation_required,
+            &ctx,
+        )?;
+        (iss.to_string(), key_thumbprint, claims)
+    };
+
+    // Credentials signed with a compromised key are only accepted if issued before the cutoff
+    if let Some(key_thumbprint) = key_thumbprint {
+        ensure_key_not_compromised(deps.storage, app_addr, &key_thumbprint, &claims)?;
     }
+
+    Ok(VerifiedPresentation { issuer, claims })
+}
+
+/// Finds the key for `iss` in the route issuers,
+/// or in the contract-wide registry entries the route references
+pub fn issuer_pubkey(
+    storage: &dyn Storage,
+    requirements: &VerificationRequirements,
+    iss: &str,
+    kid: Option<&str>,
+) -> Result<Jwk, SdjwtVerifierResultError> {
+    // Issuers with several keys select one of them with the `kid`
+    if let Some(pubkey) = kid
+        .and_then(|kid| requirements.issuer_key_ids.get(&issuer_key_id(iss, kid)))
+        .or_else(|| {
+            requirements
+                .issuer_pubkeys
+                .as_ref()
+                .and_then(|pubkeys| pubkeys.get(iss))
+        })
+    {
+        return Ok(pubkey.clone());
+    }
+
+    if let Some(pubkey) = registry_issuer_pubkey(storage, &requirements.registry_issuers, iss)? {
+        return Ok(pubkey);
+    }
+
     // If the issuer is not in the requirements, we return an error
-    else {
+    if requirements.issuer_pubkeys.is_none() && requirements.registry_issuers.is_empty() {
         Err(SdjwtVerifierResultError::IssuerNotFound)
+    } else {
+        Err(SdjwtVerifierResultError::PubKeyNotFound)
     }
 }
 
+/// Verifies the presentation signature with the issuer key
+/// and validates the verified claims against the route and additional requirements
+fn verify_and_validate(
+    presentation: String,
+    decoding_key: DecodingKey,
+    presentation_required: &PresentationReq,
+    ctx: &ValidationContext,
+) -> Result<Value, SdjwtVerifierResultError> {
+    // We verify the presentation
+    let sdjwt_verifier = SDJWTVerifier::new(
+        presentation,
+        Box::new(move |_, _| decoding_key.clone()),
+        None, // This version does not support key binding
+        None, // This version does not support key binding
+        SDJWTSerializationFormat::Compact,
+    )
+    .map_err(|e| SdjwtVerifierResultError::SdJwt(e.to_string()))?;
+
+    // We validate the verified claims against the requirements
+    validate(
+        presentation_required.clone(),
+        sdjwt_verifier.verified_claims.clone(),
+        ctx,
+    )?;
+
+    Ok(sdjwt_verifier.verified_claims)
+}
+
 /// Performs a registration of an application and all its routes
 pub fn _register(
     storage: &mut dyn Storage,
@@ -399,18 +721,16 @@ pub fn _register(
         // Make a verification request for specified app addr and route id with a provided route criteria
         let _RegistrationRequest {
             verification_requirements,
-            ibc_msgs,
+            resource_requests,
         } = make_internal_registration_request(storage, env, app_addr, route_id, requirements)?;
 
         // Save the registered trust data sources and route requirements
-        APP_ROUTES_REQUIREMENTS.save(
-            storage,
-            (app_addr.to_owned(), route_id),
-            &verification_requirements,
-        )?;
+        save_route_requirements(storage, app_addr, route_id, verification_requirements)?;
 
-        if let Some(ibc_msgs) = ibc_msgs {
-            response = response.add_submessages(ibc_msgs);
+        // Cached resources are served to the saved route
+        for (resource_req, pending_route) in resource_requests {
+            let request = make_resource_request(storage, env, resource_req, pending_route)?;
+            response = extend_response(response, request);
         }
     }
 
@@ -420,15 +740,105 @@ pub fn _register(
 }
 
 /// Performs a deregister of an application and all its routes
-fn _deregister(storage: &mut dyn Storage, app_addr: &str) -> Result<Response, SdjwtVerifierError> {
-    APP_ROUTES_REQUIREMENTS
+fn _deregister(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: &str,
+) -> Result<Response, SdjwtVerifierError> {
+    let routes: Vec<RouteId> = APP_ROUTES_REQUIREMENTS
         .prefix(app_addr.to_owned())
-        .clear(storage, None);
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<Result<Vec<_>, _>>()?;
+    for route_id in routes {
+        remove_route_requirements(storage, env, app_addr, route_id)?;
+    }
     APP_ADMINS.remove(storage, app_addr);
 
     Ok(Response::default())
 }
 
+/// Saves the requirements of a registered or updated route.
+/// Its `idx` revocation list is moved out of the requirements
+/// and replaces the revocations of all the route issuers
+fn save_route_requirements(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    mut requirements: VerificationRequirements,
+) -> StdResult<()> {
+    let revoked = take_inline_revocations(&mut requirements);
+    replace_revocations(storage, app_addr, route_id, &revoked)?;
+    store_route_requirements(storage, app_addr, route_id, &requirements)
+}
+
+/// Stores the route requirements, keeping the issuer to routes index in sync
+fn store_route_requirements(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+    requirements: &VerificationRequirements,
+) -> StdResult<()> {
+    remove_issuer_routes(storage, app_addr, route_id)?;
+    for iss in requirements.issuer_pubkeys.iter().flat_map(HashMap::keys) {
+        ISSUER_ROUTES.save(storage, (iss, app_addr, route_id), &Empty {})?;
+    }
+    APP_ROUTES_REQUIREMENTS.save(storage, (app_addr.to_owned(), route_id), requirements)
+}
+
+/// Removes the route requirements, its revocations, status lists, key sources and fetch failures
+/// and the route from the issuer to routes index
+fn remove_route_requirements(
+    storage: &mut dyn Storage,
+    env: &Env,
+    app_addr: &str,
+    route_id: RouteId,
+) -> StdResult<()> {
+    remove_issuer_routes(storage, app_addr, route_id)?;
+    clear_revocations(storage, app_addr, route_id)?;
+    clear_revocation_roots(storage, app_addr, route_id);
+    clear_status_lists(storage, app_addr, route_id)?;
+    clear_fetch_failures(storage, app_addr, route_id)?;
+    clear_key_sources(storage, app_addr, route_id)?;
+    clear_key_versions(storage, env, app_addr, route_id)?;
+    APP_ROUTES_REQUIREMENTS.remove(storage, (app_addr.to_owned(), route_id));
+    Ok(())
+}
+
+/// Removes the route from the issuer to routes index
+fn remove_issuer_routes(
+    storage: &mut dyn Storage,
+    app_addr: &str,
+    route_id: RouteId,
+) -> StdResult<()> {
+    if let Some(requirements) =
+        APP_ROUTES_REQUIREMENTS.may_load(storage, (app_addr.to_owned(), route_id))?
+    {
+        for iss in requirements.issuer_pubkeys.iter().flat_map(HashMap::keys) {
+            ISSUER_ROUTES.remove(storage, (iss, app_addr, route_id));
+        }
+    }
+    Ok(())
+}
+
+/// Migrates the routes stored by earlier versions of the contract,
+/// moving their `idx` revocation lists out of the requirements and indexing them by issuer.
+/// Returns the number of migrated routes
+pub fn migrate_routes(storage: &mut dyn Storage) -> StdResult<u64> {
+    let routes = APP_ROUTES_REQUIREMENTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let migrated = routes.len() as u64;
+    for ((app_addr, route_id), mut requirements) in routes {
+        let revoked: Vec<(IdxRange, bool)> = take_inline_revocations(&mut requirements)
+            .into_iter()
+            .map(|idx| (IdxRange::from(idx), true))
+            .collect();
+        update_revocations(storage, &app_addr, route_id, None, &revoked)?;
+        store_route_requirements(storage, &app_addr, route_id, &requirements)?;
+    }
+    Ok(migrated)
+}
+
 /// Performs an update on the verification requirements for a given app addr and route id with the new criteria
 fn _update(
     storage: &mut dyn Storage,
@@ -454,22 +864,19 @@ fn _update(
         // Make a verification request for specified app addr and route id with a provided route criteria
         let _RegistrationRequest {
             verification_requirements,
-            ibc_msgs,
+            resource_requests,
         } = make_internal_registration_request(storage, env, app_addr, route_id, route_criteria)?;
 
-        APP_ROUTES_REQUIREMENTS.save(
-            storage,
-            (app_addr.to_owned(), route_id),
-            &verification_requirements,
-        )?;
+        save_route_requirements(storage, app_addr, route_id, verification_requirements)?;
 
-        if let Some(ibc_msgs) = ibc_msgs {
-            response = response.add_submessages(ibc_msgs);
+        for (resource_req, pending_route) in resource_requests {
+            let request = make_resource_request(storage, env, resource_req, pending_route)?;
+            response = extend_response(response, request);
         }
 
         Ok(response)
     } else {
-        APP_ROUTES_REQUIREMENTS.remove(storage, (app_addr.to_owned(), route_id));
+        remove_route_requirements(storage, env, app_addr, route_id)?;
 
         if APP_ROUTES_REQUIREMENTS
             .prefix(app_addr.to_owned())
@@ -477,7 +884,7 @@ fn _update(
             .next()
             .is_none()
         {
-            _deregister(storage, app_addr)
+            _deregister(storage, env, app_addr)
         } else {
             Ok(response)
         }
@@ -492,109 +899,452 @@ fn make_internal_registration_request(
     route_id: RouteId,
     route_criteria: RouteVerificationRequirements,
 ) -> Result<_RegistrationRequest, SdjwtVerifierError> {
-    let mut ibc_submsgs: Vec<SubMsg> = Vec::new();
-    let mut issuer_pubkeys: HashMap<String, Jwk> = HashMap::new();
+    let mut resource_requests = Vec::new();
+    let mut inline_keys = IssuerKeys::default();
 
     let mut vr = VerificationRequirements::new(route_criteria.presentation_required, None)?;
 
+    // The keys of the previous sources are replaced
+    clear_key_sources(storage, app_addr, route_id)?;
+    clear_key_versions(storage, env, app_addr, route_id)?;
+
     for isd in route_criteria.issuer_source_or_data.iter() {
         if let Some(registry) = &isd.source {
             match registry {
                 // We query this data via IBC
                 TrustRegistry::Cheqd => {
-                    // For Cheqd, the data is in the ResourceReqPacket
-                    let resource_req_packat: ResourceReqPacket = from_json(&isd.data_or_location)?;
-
-                    let ibc_msg = SubMsg::new(CosmosMsg::Ibc(cosmwasm_std::IbcMsg::SendPacket {
-                        channel_id: CHANNEL_ID.load(storage)?,
-                        data: to_json_binary(&resource_req_packat)?,
-                        timeout: IbcTimeout::with_timestamp(get_timeout_timestamp(
-                            env,
-                            HOUR_PACKET_LIFETIME,
-                        )),
-                    }));
-
-                    PENDING_VERIFICATION_REQ_REQUESTS.save(
+                    // For Cheqd, the data is the requested resource and its accepted versions
+                    let key_source = CheqdKeySource::parse(&isd.data_or_location)?;
+                    ensure!(
+                        key_source
+                            .max_age_days
+                            .is_none_or(|days| days <= MAX_RESOURCE_AGE_DAYS),
+                        SdjwtVerifierError::ResourceMaxAgeTooLarge(MAX_RESOURCE_AGE_DAYS)
+                    );
+                    if let (Some(network), Some(expected)) =
+                        (key_source.network, CHEQD_NETWORK.may_load(storage)?)
+                    {
+                        ensure!(
+                            network == expected,
+                            SdjwtVerifierError::CheqdNetworkMismatch(
+                                expected.to_string(),
+                                network.to_string()
+                            )
+                        );
+                    }
+                    let resource_req_packat = key_source.resource_req();
+                    save_key_source(
                         storage,
-                        &resource_req_packat.to_string(),
-                        &PendingRoute {
+                        app_addr,
+                        route_id,
+                        key_source.route_key_source(env.block.time),
+                    )?;
+
+                    resource_requests.push((
+                        resource_req_packat,
+                        PendingRoute {
                             app_addr: app_addr.to_string(),
                             route_id,
+                            resource: PendingResource::IssuerKeys,
+                            source: None,
                         },
-                    )?;
-
-                    ibc_submsgs.push(ibc_msg);
+                    ));
+                }
+                // Root CA certificates are directly provided as the trust anchor for `x5c`
+                TrustRegistry::X509 => {
+                    validate_root_certificate(&isd.data_or_location)?;
+                    vr.trusted_roots.push(isd.data_or_location.clone());
+                }
+                // The keys are maintained in the contract-wide registry
+                TrustRegistry::IssuerRegistry => {
+                    let name: String = from_json(&isd.data_or_location)?;
+                    if !TRUSTED_ISSUERS.has(storage, &name) {
+                        return Err(SdjwtVerifierError::TrustedIssuerNotFound(name));
+                    }
+                    vr.registry_issuers.push(name);
                 }
             }
         } else {
             let issuer_pubkey_info: JwkInfo = from_json(&isd.data_or_location)?;
-            let pubkey: Jwk = from_json(&issuer_pubkey_info.jwk)?;
-
-            if let AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
-                curve: EllipticCurve::Ed25519,
-                ..
-            }) = pubkey.algorithm
-            {
-                issuer_pubkeys.insert(issuer_pubkey_info.issuer, pubkey);
-            } else {
-                return Err(SdjwtVerifierError::UnsupportedKeyType);
-            }
+            let pubkey = parse_issuer_jwk(&issuer_pubkey_info.jwk)?;
+            inline_keys
+                .issuers
+                .insert(issuer_pubkey_info.issuer, pubkey);
         }
     }
 
-    // Update vr with the latest issuer_pubkeys
-    vr.issuer_pubkeys = issuer_pubkeys.into();
+    // Update vr with the latest issuer_pubkeys, the fetched keys are merged in on ack
+    let keys = merge_source_keys(storage, app_addr, route_id, INLINE_KEYS_SOURCE, inline_keys)?;
+    vr.issuer_pubkeys = Some(keys.issuers);
+    vr.issuer_key_ids = keys.key_ids;
 
-    if !ibc_submsgs.is_empty() {
-        Ok(_RegistrationRequest::new(vr, Some(ibc_submsgs)))
+    ensure_shared_revocation_lists_exist(storage, &vr)?;
+
+    Ok(_RegistrationRequest::new(vr, resource_requests))
+}
+
+/// Requests a resource from cheqd for the pending route.
+/// Previous failures of the fetch are cleared, so it gets a new set of retries
+pub fn make_resource_request(
+    storage: &mut dyn Storage,
+    env: &Env,
+    resource_req_packet: ResourceReqPacket,
+    pending_route: PendingRoute,
+) -> Result<Response, SdjwtVerifierError> {
+    clear_fetch_failure(
+        storage,
+        &pending_route.app_addr,
+        pending_route.route_id,
+        &resource_req_packet,
+    )?;
+    send_resource_request(storage, env, resource_req_packet, pending_route)
+}
+
+/// Serves a cached resource to the pending route, unless its key source follows the latest
+/// version, whose `next_version_id` may have changed since the resource was cached.
+/// Otherwise the resource is fetched over IBC
+pub fn send_resource_request(
+    storage: &mut dyn Storage,
+    env: &Env,
+    resource_req_packet: ResourceReqPacket,
+    pending_route: PendingRoute,
+) -> Result<Response, SdjwtVerifierError> {
+    let resource_key = resource_req_packet.to_string();
+
+    let key_source = pending_key_source(storage, &resource_req_packet, &pending_route)?;
+    let follows_latest = key_source
+        .as_ref()
+        .is_some_and(|key_source| key_source.version == ResourceVersion::Latest);
+    if !follows_latest {
+        if let Some(resource) = RESOURCE_CACHE.may_load(storage, &resource_key)? {
+            return serve_resource(
+                storage,
+                env,
+                &resource_req_packet,
+                pending_route,
+                key_source,
+                resource,
+            );
+        }
+    }
+
+    subscribe_resource_request(storage, env, resource_req_packet, pending_route)
+}
+
+/// Subscribes the pending route to the request of the resource in flight,
+/// or sends the IBC packet of the request when there is none
+pub fn subscribe_resource_request(
+    storage: &mut dyn Storage,
+    env: &Env,
+    resource_req_packet: ResourceReqPacket,
+    pending_route: PendingRoute,
+) -> Result<Response, SdjwtVerifierError> {
+    let resource_key = resource_req_packet.to_string();
+
+    let subscribers = PENDING_VERIFICATION_REQ_REQUESTS.may_load(storage, &resource_key)?;
+    let in_flight = subscribers.is_some();
+    let mut subscribers = subscribers.unwrap_or_default();
+    if !subscribers.contains(&pending_route) {
+        subscribers.push(pending_route);
+    }
+    PENDING_VERIFICATION_REQ_REQUESTS.save(storage, &resource_key, &subscribers)?;
+
+    if in_flight {
+        return Ok(Response::new());
+    }
+    // Requests made while no channel is active are sent once one is
+    let Some(channel_id) = active_channel(storage)? else {
+        QUEUED_RESOURCE_REQUESTS.save(storage, &resource_key, &resource_req_packet)?;
+        return Ok(Response::new().add_attribute("queued", resource_key));
+    };
+    Ok(Response::new().add_submessage(resource_packet(channel_id, env, &resource_req_packet)?))
+}
+
+/// The IBC packet of a resource request
+pub fn resource_packet(
+    channel_id: String,
+    env: &Env,
+    resource_req_packet: &ResourceReqPacket,
+) -> StdResult<SubMsg> {
+    Ok(SubMsg::new(CosmosMsg::Ibc(
+        cosmwasm_std::IbcMsg::SendPacket {
+            channel_id,
+            data: to_json_binary(resource_req_packet)?,
+            timeout: IbcTimeout::with_timestamp(get_timeout_timestamp(env, HOUR_PACKET_LIFETIME)),
+        },
+    )))
+}
+
+/// Adds the messages, attributes and events of a response to another
+pub fn extend_response(res: Response, other: Response) -> Response {
+    res.add_submessages(other.messages)
+        .add_attributes(other.attributes)
+        .add_events(other.events)
+}
+
+/// Parses a directly provided issuer JWK, only Ed25519 keys are supported
+pub fn parse_issuer_jwk(jwk: &Binary) -> Result<Jwk, SdjwtVerifierError> {
+    let pubkey: Jwk = from_json(jwk)?;
+
+    if let AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+        curve: EllipticCurve::Ed25519,
+        ..
+    }) = pubkey.algorithm
+    {
+        Ok(pubkey)
     } else {
-        Ok(_RegistrationRequest::new(vr, None))
+        Err(SdjwtVerifierError::UnsupportedKeyType)
     }
 }
 
-// Functions in the `impl` block has access to the state of the contract
-pub fn ibc_channel_connect_handler(
+/// Handles the resource returned for a pending request, which is cached and served
+/// to every subscribed route. Error acks and resources that cannot be used are recorded
+/// as a failed fetch of the routes, so that the ack itself does not fail
+/// and the pending request is cleaned up.
+/// The acks of the packets sent on a replaced or closed channel are ignored,
+/// their requests were already recorded as failed
+pub fn ibc_packet_ack_handler(
     deps: DepsMut,
-    msg: IbcChannelConnectMsg,
+    env: Env,
+    msg: IbcPacketAckMsg,
 ) -> Result<IbcBasicResponse, SdjwtVerifierError> {
-    if CHANNEL_ID.may_load(deps.storage)?.is_some() {
-        Err(SdjwtVerifierError::ChannelAlreadyExists)
-    } else {
-        CHANNEL_ID.save(deps.storage, &msg.channel().endpoint.channel_id)?;
+    if !is_active_channel(deps.storage, &msg.original_packet.src.channel_id)? {
+        return Ok(IbcBasicResponse::new()
+            .add_attribute("action", "ibc_packet_ack")
+            .add_attribute("ignored", "inactive_channel"));
+    }
+    let resource_req_packet: ResourceReqPacket = from_json(&msg.original_packet.data)?;
+    let resource_key = resource_req_packet.to_string();
 
-        Ok(IbcBasicResponse::new())
+    // Checks that this was a packet that we requested. A request failed when the channel was
+    // replaced has no pending routes anymore, its late ack is ignored
+    let Some(subscribers) =
+        PENDING_VERIFICATION_REQ_REQUESTS.may_load(deps.storage, &resource_key)?
+    else {
+        return Ok(IbcBasicResponse::new()
+            .add_attribute("action", "ibc_packet_ack")
+            .add_attribute("ignored", "no_pending_request"));
+    };
+    PENDING_VERIFICATION_REQ_REQUESTS.remove(deps.storage, &resource_key);
+
+    let mut res = Response::new();
+    match ibc_packet_ack_resource_extractor(msg, &ResourceRequirements::default()) {
+        Ok((_, resource)) => {
+            RESOURCE_CACHE.save(deps.storage, &resource_key, &resource)?;
+            for pending_route in subscribers {
+                let key_source =
+                    pending_key_source(deps.storage, &resource_req_packet, &pending_route)?;
+                let served = serve_resource(
+                    deps.storage,
+                    &env,
+                    &resource_req_packet,
+                    pending_route,
+                    key_source,
+                    resource.clone(),
+                )?;
+                res = extend_response(res, served);
+            }
+        }
+        Err(err) => {
+            let reason = SdjwtVerifierError::from(err).to_string();
+            for pending_route in subscribers {
+                let event = record_fetch_failure(
+                    deps.storage,
+                    &env,
+                    resource_req_packet.clone(),
+                    pending_route,
+                    reason.clone(),
+                )?;
+                res = res.add_event(event);
+            }
+        }
     }
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "ibc_packet_ack")
+        .add_submessages(res.messages)
+        .add_attributes(res.attributes)
+        .add_events(res.events))
 }
 
-pub fn ibc_packet_ack_handler(
+/// Serves a fetched resource to a route, checked against the requirements of its key source.
+/// A resource the route cannot use is recorded as a failed fetch of the route
+fn serve_resource(
+    storage: &mut dyn Storage,
+    env: &Env,
+    resource_req: &ResourceReqPacket,
+    pending_route: PendingRoute,
+    key_source: Option<RouteKeySource>,
+    resource: ResourceWithMetadata,
+) -> Result<Response, SdjwtVerifierError> {
+    let requirements = key_source
+        .as_ref()
+        .map(|key_source| key_source.requirements.clone())
+        .unwrap_or_default();
+
+    let res = requirements
+        .check(&resource.linked_resource_metadata)
+        .map_err(SdjwtVerifierError::from)
+        .and_then(|_| {
+            _handle_fetched_resource(
+                storage,
+                env,
+                resource_req,
+                &pending_route,
+                key_source,
+                resource,
+            )
+        });
+
+    match res {
+        Ok(res) => {
+            clear_fetch_failure(
+                storage,
+                &pending_route.app_addr,
+                pending_route.route_id,
+                resource_req,
+            )?;
+            Ok(res)
+        }
+        Err(err) => {
+            let event = record_fetch_failure(
+                storage,
+                env,
+                resource_req.clone(),
+                pending_route,
+                err.to_string(),
+            )?;
+            Ok(Response::new().add_event(event))
+        }
+    }
+}
+
+/// Records the fetch of a pending request as failed for every subscribed route when its packet times out,
+/// unless it was sent on a replaced or closed channel
+pub fn ibc_packet_timeout_handler(
     deps: DepsMut,
-    msg: IbcPacketAckMsg,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
 ) -> Result<IbcBasicResponse, SdjwtVerifierError> {
-    let (resource_req_packet, resource) = ibc_packet_ack_resource_extractor(msg)?;
+    if !is_active_channel(deps.storage, &msg.packet.src.channel_id)? {
+        return Ok(IbcBasicResponse::new()
+            .add_attribute("action", "ibc_packet_timeout")
+            .add_attribute("ignored", "inactive_channel"));
+    }
+    let resource_req_packet: ResourceReqPacket = from_json(&msg.packet.data)?;
 
-    // Checks that this was a packet that we requested
-    let pending_route =
-        PENDING_VERIFICATION_REQ_REQUESTS.load(deps.storage, &resource_req_packet.to_string())?;
-    PENDING_VERIFICATION_REQ_REQUESTS.remove(deps.storage, &resource_req_packet.to_string());
+    let resource_key = resource_req_packet.to_string();
 
-    // Checks the return data is the expected format
-    let pubkeys: HashMap<String, Jwk> = from_json(resource.linked_resource.data)
-        .map_err(|e| SdjwtVerifierError::ReturnedResourceFormat(e.to_string()))?;
+    let mut res = IbcBasicResponse::new().add_attribute("action", "ibc_packet_timeout");
+    let subscribers = PENDING_VERIFICATION_REQ_REQUESTS
+        .may_load(deps.storage, &resource_key)?
+        .unwrap_or_default();
+    PENDING_VERIFICATION_REQ_REQUESTS.remove(deps.storage, &resource_key);
+
+    for pending_route in subscribers {
+        let event = record_fetch_failure(
+            deps.storage,
+            &env,
+            resource_req_packet.clone(),
+            pending_route,
+            "IBC packet timed out".to_string(),
+        )?;
+        res = res.add_event(event);
+    }
+    Ok(res)
+}
 
+/// Stores a resource fetched for a route, either its issuer keys or a status list
+fn _handle_fetched_resource(
+    storage: &mut dyn Storage,
+    env: &Env,
+    resource_req: &ResourceReqPacket,
+    pending_route: &PendingRoute,
+    key_source: Option<RouteKeySource>,
+    resource: ResourceWithMetadata,
+) -> Result<Response, SdjwtVerifierError> {
     let mut req = APP_ROUTES_REQUIREMENTS
         .load(
-            deps.storage,
+            storage,
             (pending_route.app_addr.clone(), pending_route.route_id),
         )
         .map_err(|_| SdjwtVerifierError::NoRequirementsForRoute)?;
 
-    req.issuer_pubkeys = Some(pubkeys);
+    if let PendingResource::StatusList = pending_route.resource {
+        let event = _update_status_list(
+            storage,
+            &env.block,
+            &pending_route.app_addr,
+            pending_route.route_id,
+            &req,
+            &resource.linked_resource.data,
+        )?;
+        return Ok(Response::new().add_event(event));
+    }
 
-    APP_ROUTES_REQUIREMENTS.save(
-        deps.storage,
-        (pending_route.app_addr, pending_route.route_id),
+    let source = pending_route.source.as_ref().unwrap_or(resource_req);
+    let (version, format, freshness) = key_source
+        .map(|key_source| (key_source.version, key_source.format, key_source.freshness))
+        .unwrap_or_default();
+
+    // The keys are taken from the latest version, which is requested in turn
+    if let Some(next_version) = check_key_version(&version, &resource.linked_resource_metadata)? {
+        let res = send_resource_request(
+            storage,
+            env,
+            next_version.clone(),
+            PendingRoute {
+                source: Some(source.clone()),
+                ..pending_route.clone()
+            },
+        )?;
+        return Ok(res.add_attribute("next_version", next_version.to_string()));
+    }
+    check_key_freshness(
+        &freshness,
+        &resource.linked_resource_metadata,
+        env.block.time,
+    )?;
+
+    // Checks the return data is the expected format
+    let pubkeys = resource_issuer_keys(
+        &format,
+        &resource.linked_resource.data,
+        &resource.linked_resource_metadata.resource_collection_id,
+    )?;
+
+    // The keys of the other sources of the route are kept
+    let keys = merge_source_keys(
+        storage,
+        &pending_route.app_addr,
+        pending_route.route_id,
+        &source.to_string(),
+        pubkeys,
+    )?;
+    req.issuer_pubkeys = Some(keys.issuers);
+    req.issuer_key_ids = keys.key_ids;
+
+    store_route_requirements(
+        storage,
+        &pending_route.app_addr,
+        pending_route.route_id,
         &req,
     )?;
+    set_key_source_fetched(
+        storage,
+        env,
+        &pending_route.app_addr,
+        pending_route.route_id,
+        source,
+    )?;
+    save_key_version(
+        storage,
+        env,
+        &pending_route.app_addr,
+        pending_route.route_id,
+        source,
+        resource.linked_resource_metadata,
+    )?;
 
-    Ok(IbcBasicResponse::new())
+    Ok(Response::new())
 }
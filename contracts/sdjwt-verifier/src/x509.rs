@@ -0,0 +1,198 @@
+use crate::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    registry::{ed25519_thumbprint, to_base64url},
+};
+use cosmwasm_std::{Api, Binary, Timestamp};
+use jsonwebtoken::DecodingKey;
+use x509_cert::{
+    der::{oid::ObjectIdentifier, Decode, Encode},
+    ext::pkix::{BasicConstraints, KeyUsage},
+    Certificate,
+};
+
+/// The Ed25519 algorithm identifier (RFC 8410), used for both the key and the signature
+pub const ED25519_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+
+/// Maximum number of certificates accepted in the `x5c` header
+pub const MAX_X5C_CHAIN_LEN: usize = 5;
+
+/// The leaf of a validated `x5c` chain
+pub struct X5cLeaf {
+    /// The key to verify the SD-JWT with
+    pub decoding_key: DecodingKey,
+    /// The RFC4514 string of the leaf certificate subject, used as the effective issuer
+    pub subject: String,
+    /// The RFC 7638 thumbprint of the leaf key
+    pub key_thumbprint: String,
+}
+
+/// Validates a root CA certificate provided on route registration
+pub fn validate_root_certificate(der: &Binary) -> Result<(), SdjwtVerifierError> {
+    let root = Certificate::from_der(der)
+        .map_err(|e| SdjwtVerifierError::InvalidRootCertificate(e.to_string()))?;
+    ed25519_public_key(&root)
+        .map_err(|e| SdjwtVerifierError::InvalidRootCertificate(e.to_string()))?;
+    check_ca(&root).map_err(|e| SdjwtVerifierError::InvalidRootCertificate(e.to_string()))
+}
+
+/// Validates the `x5c` chain (leaf first, as per RFC 7515) from the presentation header
+/// against the trusted root certificates of the route.
+///
+/// Each certificate must be within its validity period at `now`,
+/// intermediates must be CAs that can sign certificates
+/// and the leaf must be allowed to create digital signatures.
+pub fn verify_x5c_chain(
+    api: &dyn Api,
+    x5c: &[String],
+    trusted_roots: &[Binary],
+    now: Timestamp,
+) -> Result<X5cLeaf, SdjwtVerifierResultError> {
+    if x5c.is_empty() || x5c.len() > MAX_X5C_CHAIN_LEN {
+        return Err(SdjwtVerifierResultError::X5cChainInvalid(format!(
+            "chain length must be between 1 and {MAX_X5C_CHAIN_LEN}"
+        )));
+    }
+
+    let chain = x5c
+        .iter()
+        .map(|c| {
+            let der = Binary::from_base64(c)
+                .map_err(|e| SdjwtVerifierResultError::X5cChainInvalid(e.to_string()))?;
+            let cert = Certificate::from_der(&der)
+                .map_err(|e| SdjwtVerifierResultError::X5cChainInvalid(e.to_string()))?;
+            Ok((der, cert))
+        })
+        .collect::<Result<Vec<_>, SdjwtVerifierResultError>>()?;
+
+    for (i, (_, cert)) in chain.iter().enumerate() {
+        check_validity(cert, now)?;
+        if i == 0 {
+            check_leaf_key_usage(cert)?;
+        } else {
+            check_ca(cert)?;
+        }
+        if let Some((_, issuer)) = chain.get(i + 1) {
+            verify_signed_by(api, cert, issuer)?;
+        }
+    }
+
+    // The chain is anchored if its last certificate is either a trusted root
+    // or is directly signed by one. Several roots can share a subject, e.g. an expired root
+    // and its renewal, so a root that is not valid or did not sign the chain is skipped
+    let (last_der, last) = chain.last().expect("chain is not empty");
+    let anchored = trusted_roots.iter().any(|root_der| {
+        root_der == last_der
+            || Certificate::from_der(root_der).is_ok_and(|root| {
+                root.tbs_certificate.subject == last.tbs_certificate.issuer
+                    && check_validity(&root, now).is_ok()
+                    && verify_signed_by(api, last, &root).is_ok()
+            })
+    });
+    if !anchored {
+        return Err(SdjwtVerifierResultError::X5cUntrustedRoot);
+    }
+
+    let leaf = &chain[0].1;
+    let public_key = ed25519_public_key(leaf)?;
+    Ok(X5cLeaf {
+        decoding_key: DecodingKey::from_ed_der(public_key),
+        subject: leaf.tbs_certificate.subject.to_string(),
+        key_thumbprint: ed25519_thumbprint(&to_base64url(public_key)),
+    })
+}
+
+fn ed25519_public_key(cert: &Certificate) -> Result<&[u8], SdjwtVerifierResultError> {
+    let spki = &cert.tbs_certificate.subject_public_key_info;
+    if spki.algorithm.oid != ED25519_OID {
+        return Err(SdjwtVerifierResultError::X5cChainInvalid(
+            "only Ed25519 certificates are supported".to_string(),
+        ));
+    }
+    Ok(spki.subject_public_key.raw_bytes())
+}
+
+fn verify_signed_by(
+    api: &dyn Api,
+    cert: &Certificate,
+    issuer: &Certificate,
+) -> Result<(), SdjwtVerifierResultError> {
+    if cert.tbs_certificate.issuer != issuer.tbs_certificate.subject {
+        return Err(SdjwtVerifierResultError::X5cChainInvalid(format!(
+            "{} is not issued by {}",
+            cert.tbs_certificate.subject, issuer.tbs_certificate.subject
+        )));
+    }
+    if cert.signature_algorithm.oid != ED25519_OID {
+        return Err(SdjwtVerifierResultError::X5cChainInvalid(
+            "only Ed25519 signatures are supported".to_string(),
+        ));
+    }
+    let tbs = cert
+        .tbs_certificate
+        .to_der()
+        .map_err(|e| SdjwtVerifierResultError::X5cChainInvalid(e.to_string()))?;
+    let signature = cert.signature.raw_bytes();
+    let valid = api
+        .ed25519_verify(&tbs, signature, ed25519_public_key(issuer)?)
+        .map_err(|e| SdjwtVerifierResultError::X5cChainInvalid(e.to_string()))?;
+    if !valid {
+        return Err(SdjwtVerifierResultError::X5cChainInvalid(format!(
+            "invalid signature on {}",
+            cert.tbs_certificate.subject
+        )));
+    }
+    Ok(())
+}
+
+fn check_validity(cert: &Certificate, now: Timestamp) -> Result<(), SdjwtVerifierResultError> {
+    let validity = &cert.tbs_certificate.validity;
+    let now = now.seconds();
+    if now < validity.not_before.to_unix_duration().as_secs()
+        || now > validity.not_after.to_unix_duration().as_secs()
+    {
+        return Err(SdjwtVerifierResultError::X5cCertificateNotValid(
+            cert.tbs_certificate.subject.to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_ca(cert: &Certificate) -> Result<(), SdjwtVerifierResultError> {
+    let subject = cert.tbs_certificate.subject.to_string();
+    let is_ca = cert
+        .tbs_certificate
+        .get::<BasicConstraints>()
+        .map_err(|e| SdjwtVerifierResultError::X5cChainInvalid(e.to_string()))?
+        .map(|(_, bc)| bc.ca)
+        .unwrap_or(false);
+    if !is_ca {
+        return Err(SdjwtVerifierResultError::X5cKeyUsage(subject));
+    }
+    // If present, the key usage must allow signing certificates
+    if let Some((_, ku)) = cert
+        .tbs_certificate
+        .get::<KeyUsage>()
+        .map_err(|e| SdjwtVerifierResultError::X5cChainInvalid(e.to_string()))?
+    {
+        if !ku.key_cert_sign() {
+            return Err(SdjwtVerifierResultError::X5cKeyUsage(subject));
+        }
+    }
+    Ok(())
+}
+
+fn check_leaf_key_usage(cert: &Certificate) -> Result<(), SdjwtVerifierResultError> {
+    // If present, the key usage must allow digital signatures
+    if let Some((_, ku)) = cert
+        .tbs_certificate
+        .get::<KeyUsage>()
+        .map_err(|e| SdjwtVerifierResultError::X5cChainInvalid(e.to_string()))?
+    {
+        if !ku.digital_signature() {
+            return Err(SdjwtVerifierResultError::X5cKeyUsage(
+                cert.tbs_certificate.subject.to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -1,15 +1,20 @@
 use avida_sdjwt_verifier::types::JwkInfo;
 use avida_sdjwt_verifier::types::NumberCriterion;
 use avida_sdjwt_verifier::types::ReqAttr;
+use avida_sdjwt_verifier::types::SmtNonMembershipProof;
 use avida_sdjwt_verifier::types::IDX;
 use cosmwasm_std::to_json_binary;
 use cosmwasm_std::BlockInfo;
 use cw_utils::Expiration;
-use jsonwebtoken::EncodingKey;
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use miniz_oxide::deflate::compress_to_vec_zlib;
 use sd_jwt_rs::issuer;
 use sd_jwt_rs::SDJWTIssuer;
 use sd_jwt_rs::{SDJWTHolder, SDJWTSerializationFormat};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::{fs, path::PathBuf};
 
 use cosmwasm_std::{Binary, Timestamp};
@@ -83,6 +88,18 @@ pub fn issuer_jwk() -> josekit::jwk::Jwk {
     key_pair.to_jwk_public_key()
 }
 
+/// Is used to get the RFC 7638 thumbprint of the ed25519 predefined issuer key
+pub fn issuer_key_thumbprint() -> String {
+    let jwk = serde_json::to_value(issuer_jwk()).unwrap();
+    let x = jwk["x"].as_str().unwrap();
+    let digest = Sha256::digest(format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{x}"}}"#));
+    Binary::from(digest.to_vec())
+        .to_base64()
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_")
+}
+
 /// Is used to get an jwk public key instance from some RSA predefined private key, read from a file
 pub fn rsa_issuer_jwk() -> josekit::jwk::Jwk {
     let mut key_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -163,6 +180,130 @@ pub fn make_presentation(
         .unwrap()
 }
 
+/// Is used to get the base64 DER encoding of a PEM certificate fixture, as used in `x5c`
+pub fn x509_cert_b64(file_name: &str) -> String {
+    let mut cert_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    cert_path = cert_path.join("fixtures").join(file_name);
+    fs::read_to_string(cert_path)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect()
+}
+
+/// Make a presentation where the issuer JWT header carries the `x5c` certificate chain
+pub fn make_x5c_presentation(claims: Value, x5c: Vec<String>) -> String {
+    let mut fx_issuer = issuer();
+    let sdjwt = fx_issuer
+        .issue_sd_jwt(
+            claims.clone(),
+            issuer::ClaimsForSelectiveDisclosureStrategy::AllLevels,
+            None,
+            false,
+            SDJWTSerializationFormat::Compact,
+        )
+        .unwrap();
+
+    // Re-sign the issuer JWT with the certificate chain in the header
+    let (jwt, disclosures) = sdjwt.split_once('~').unwrap();
+    let mut header = decode_header(jwt).unwrap();
+    header.x5c = Some(x5c);
+
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+    let payload = decode::<Value>(jwt, &DecodingKey::from_secret(&[]), &validation)
+        .unwrap()
+        .claims;
+
+    let mut key_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    key_path = key_path.join("fixtures/test_ed25519_private.pem");
+    let encoding_key = EncodingKey::from_ed_pem(&fs::read(key_path).unwrap()).unwrap();
+    let jwt = encode(&header, &payload, &encoding_key).unwrap();
+
+    let c = claims.as_object().unwrap().clone();
+    let mut holder = SDJWTHolder::new(
+        format!("{jwt}~{disclosures}"),
+        SDJWTSerializationFormat::Compact,
+    )
+    .unwrap();
+    holder
+        .create_presentation(c, None, None, None, None)
+        .unwrap()
+}
+
+/// Make a Token Status List JWT signed by the predefined issuer key,
+/// `lst` is the uncompressed list of `bits` sized statuses
+pub fn make_status_list_token(uri: &str, iat: u64, bits: u8, lst: &[u8]) -> String {
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.typ = Some("statuslist+jwt".to_string());
+
+    let lst = Binary::from(compress_to_vec_zlib(lst, 6))
+        .to_base64()
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_");
+    let claims = serde_json::json!({
+        "iss": "issuer",
+        "sub": uri,
+        "iat": iat,
+        "status_list": { "bits": bits, "lst": lst }
+    });
+
+    let mut key_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    key_path = key_path.join("fixtures/test_ed25519_private.pem");
+    let encoding_key = EncodingKey::from_ed_pem(&fs::read(key_path).unwrap()).unwrap();
+    encode(&header, &claims, &encoding_key).unwrap()
+}
+
+/// Builds the revocation sparse Merkle tree over the `revoked` idx,
+/// returns its root and the non-membership proof of `idx`
+pub fn make_revocation_root_and_proof(
+    revoked: &[u64],
+    idx: u64,
+) -> (Binary, SmtNonMembershipProof) {
+    let mut empty = [[0u8; 32]; 65];
+    for level in 1..65 {
+        empty[level] = hash_pair(&empty[level - 1], &empty[level - 1]);
+    }
+
+    let siblings = (0..64)
+        .map(|level| {
+            let sibling = smt_node(revoked, &empty, level, (idx >> level) ^ 1);
+            (sibling != empty[level]).then(|| Binary::from(sibling.to_vec()))
+        })
+        .collect();
+    let root = smt_node(revoked, &empty, 64, 0);
+
+    (
+        Binary::from(root.to_vec()),
+        SmtNonMembershipProof { siblings },
+    )
+}
+
+/// The node at `level` covering the idx with `idx >> level == prefix`
+fn smt_node(revoked: &[u64], empty: &[[u8; 32]; 65], level: usize, prefix: u64) -> [u8; 32] {
+    if !revoked.iter().any(|r| level == 64 || r >> level == prefix) {
+        return empty[level];
+    }
+    if level == 0 {
+        return Sha256::digest(prefix.to_be_bytes()).into();
+    }
+    hash_pair(
+        &smt_node(revoked, empty, level - 1, prefix << 1),
+        &smt_node(revoked, empty, level - 1, (prefix << 1) | 1),
+    )
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
 /// Is used to get route verification requirements
 pub fn make_route_verification_requirements(
     presentation_req: PresentationReq,
@@ -1,18 +1,18 @@
-use cosmwasm_std::{from_json, to_json_binary, Binary};
+use cosmwasm_std::{from_json, to_json_binary, Addr, Binary};
 use cw_multi_test::{App, Executor};
 
 use avida_sdjwt_verifier::errors::SdjwtVerifierResultError;
 use avida_sdjwt_verifier::types::{Criterion, ReqAttr, VerificationRequirements, VerifyResult};
 use serde::{Deserialize, Serialize};
 
-use super::fixtures::default_instantiate_verifier_contract;
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
 use crate::sdjwt::fixtures::{
     claims_with_revocation_idx, get_route_requirement_with_empty_revocation_list,
     make_presentation, PresentationVerificationType, FIRST_CALLER_APP_ADDR, FIRST_ROUTE_ID,
 };
 use avida_common::types::AvidaVerifierExecuteMsg;
 use avida_common::types::UpdateRevocationListRequest;
-use avida_sdjwt_verifier::msg::QueryMsg;
+use avida_sdjwt_verifier::msg::{ExecuteMsg, QueryMsg, SdjwtVerifierExecuteMsg};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -74,6 +74,9 @@ fn test_update_revocation_list() {
             route_id: REVOCATION_ROUTE_ID,
             revoke: vec![1, 2, 3],
             unrevoke: vec![4, 5],
+            revoke_ranges: vec![],
+            unrevoke_ranges: vec![],
+            iss: None,
         },
     };
     app.execute_contract(
@@ -104,6 +107,7 @@ fn test_update_revocation_list() {
         revocation_list.criterion,
         Criterion::NotContainedIn(vec![1, 2, 3])
     );
+    assert_revoked(&mut app, &contract_addr, &[1, 2, 3], &[4, 5]);
 
     let update_revocation_list_msg = AvidaVerifierExecuteMsg::UpdateRevocationList {
         app_addr: revocation_test_caller.to_string(),
@@ -111,6 +115,9 @@ fn test_update_revocation_list() {
             route_id: REVOCATION_ROUTE_ID,
             revoke: vec![7, 1, 7],
             unrevoke: vec![2, 5],
+            revoke_ranges: vec![],
+            unrevoke_ranges: vec![],
+            iss: None,
         },
     };
 
@@ -122,6 +129,8 @@ fn test_update_revocation_list() {
     )
     .unwrap();
 
+    assert_revoked(&mut app, &contract_addr, &[1, 3, 7], &[2, 4, 5]);
+
     let req: VerificationRequirements = app
         .wrap()
         .query_wasm_smart(
@@ -144,6 +153,38 @@ fn test_update_revocation_list() {
     );
 }
 
+fn assert_revoked(app: &mut App, contract_addr: &Addr, revoked: &[u64], unrevoked: &[u64]) {
+    let revocation_test_caller = app.api().addr_make(REVOCATION_TEST_CALLER);
+
+    for (idx, is_revoked) in revoked
+        .iter()
+        .map(|idx| (*idx, true))
+        .chain(unrevoked.iter().map(|idx| (*idx, false)))
+    {
+        let presentation = make_presentation(
+            claims_with_revocation_idx("Alice", 30, true, 2021, None, idx),
+            PresentationVerificationType::Success,
+        );
+        let res = verify_presentation(
+            app,
+            contract_addr,
+            &revocation_test_caller,
+            REVOCATION_ROUTE_ID,
+            &presentation,
+            None,
+        );
+
+        if is_revoked {
+            assert_eq!(
+                res.error.unwrap(),
+                SdjwtVerifierResultError::IdxRevoked(idx).to_string()
+            );
+        } else {
+            assert!(res.success);
+        }
+    }
+}
+
 #[test]
 fn test_revoked_presentation_cannot_be_used() {
     let revoked_idx = 111;
@@ -180,6 +221,9 @@ fn test_revoked_presentation_cannot_be_used() {
             route_id: REVOCATION_ROUTE_ID,
             revoke: vec![revoked_idx],
             unrevoke: vec![unrevoked_idx],
+            revoke_ranges: vec![],
+            unrevoke_ranges: vec![],
+            iss: None,
         },
     };
 
@@ -316,3 +360,32 @@ fn test_addition_requirements_with_revocation_list() {
 
     assert!(res.success);
 }
+
+#[test]
+fn execute_msg_keeps_the_common_message_format() {
+    let msg: ExecuteMsg = from_json(r#"{"deregister":{"app_addr":"app"}}"#).unwrap();
+    assert_eq!(
+        msg,
+        AvidaVerifierExecuteMsg::Deregister {
+            app_addr: "app".to_string()
+        }
+        .into()
+    );
+
+    let msg: ExecuteMsg = from_json(r#"{"update_owner":{"new_owner":"owner"}}"#).unwrap();
+    assert_eq!(
+        msg,
+        SdjwtVerifierExecuteMsg::UpdateOwner {
+            new_owner: "owner".to_string()
+        }
+        .into()
+    );
+
+    let verify = r#"{"presentation":"","route_id":1,"app_addr":null,"additional_requirements":null,"revocation_proof":""}"#;
+    let msg: ExecuteMsg =
+        from_json(format!(r#"{{"verify_with_revocation_proof":{verify}}}"#)).unwrap();
+    assert!(matches!(
+        msg,
+        ExecuteMsg::SdjwtVerifier(SdjwtVerifierExecuteMsg::VerifyWithRevocationProof { .. })
+    ));
+}
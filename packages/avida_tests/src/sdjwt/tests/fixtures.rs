@@ -4,21 +4,45 @@ use crate::sdjwt::fixtures::{
     VERIFIER_CONTRACT_LABEL,
 };
 
-use avida_common::types::{RegisterRouteRequest, RouteVerificationRequirements};
+use avida_cheqd::did_url::CheqdNetwork;
+use avida_cheqd::ibc::resource_checksum;
+use avida_cheqd::types::{
+    LinkedResource, LinkedResourceMetadata, ResourceReqPacket, ResourceWithMetadata,
+};
+use avida_common::types::{
+    AvidaVerifierExecuteMsg, IssuerSourceOrData, RegisterRouteRequest, RouteId,
+    RouteVerificationRequirements, TrustRegistry,
+};
 
-use cosmwasm_std::{Addr, Empty};
+use cosmwasm_std::testing::{
+    message_info, mock_dependencies, mock_env, mock_ibc_channel_connect_ack, MockApi, MockQuerier,
+    MockStorage,
+};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, Empty, IbcAcknowledgement, IbcOrder, OwnedDeps,
+    Response, StdAck,
+};
 use cw_multi_test::{App as MtApp, Contract, ContractWrapper, Executor};
 
 use avida_sdjwt_verifier::contract;
-use avida_sdjwt_verifier::msg::InstantiateMsg;
-use avida_sdjwt_verifier::types::InitRegistration;
+use avida_sdjwt_verifier::msg::{ExecuteMsg, InstantiateMsg, SdjwtVerifierExecuteMsg};
+use avida_sdjwt_verifier::types::{InitRegistration, SmtNonMembershipProof, VerifyResult};
+
+pub type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+/// The channel to the cheqd resource module in the IBC tests
+pub const CHEQD_CHANNEL_ID: &str = "channel-0";
+pub const CHEQD_COLLECTION_ID: &str = "5rjaLzcffhGUH4nt4fyfAg";
+pub const CHEQD_RESOURCE_ID: &str = "9fbb1b86-91f8-4942-97b9-725b7714131c";
+/// An Ed25519 issuer key published as a cheqd resource, keyed by the issuer `iss`
+pub const CHEQD_ISSUER_KEYS: &str =
+    r#"{"issuer":{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}}"#;
 
 fn verifier_contract() -> Box<dyn Contract<Empty>> {
-    Box::new(ContractWrapper::new_with_empty(
-        contract::execute,
-        contract::instantiate,
-        contract::query,
-    ))
+    Box::new(
+        ContractWrapper::new_with_empty(contract::execute, contract::instantiate, contract::query)
+            .with_migrate(|deps, env, msg, _info| contract::migrate(deps, env, msg)),
+    )
 }
 
 /// Is used to instantiate verifier contract with some predefined parameters
@@ -45,17 +69,145 @@ pub fn default_instantiate_verifier_contract(
     let instantiate_msg = InstantiateMsg {
         max_presentation_len: MAX_PRESENTATION_LEN,
         init_registrations,
+        cheqd_network: None,
     };
     let owner = app.api().addr_make(OWNER_ADDR);
     let contract_addr = app
         .instantiate_contract(
             code_id,
-            owner,
+            owner.clone(),
             &instantiate_msg,
             &[],
             VERIFIER_CONTRACT_LABEL,
-            None,
+            Some(owner.to_string()),
         )
         .unwrap();
     (contract_addr, fx_route_verification_req)
 }
+
+/// Verifies the presentation on the route of `app_addr`, sent by the app itself
+pub fn verify_presentation(
+    app: &mut MtApp,
+    contract_addr: &Addr,
+    app_addr: &Addr,
+    route_id: RouteId,
+    presentation: &str,
+    revocation_proof: Option<&SmtNonMembershipProof>,
+) -> VerifyResult {
+    let presentation = Binary::from(presentation.as_bytes());
+    let msg: ExecuteMsg = match revocation_proof {
+        None => AvidaVerifierExecuteMsg::Verify {
+            presentation,
+            route_id,
+            app_addr: Some(app_addr.to_string()),
+            additional_requirements: None,
+        }
+        .into(),
+        Some(proof) => SdjwtVerifierExecuteMsg::VerifyWithRevocationProof {
+            presentation,
+            route_id,
+            app_addr: Some(app_addr.to_string()),
+            additional_requirements: None,
+            revocation_proof: to_json_binary(proof).unwrap(),
+        }
+        .into(),
+    };
+    from_json(
+        app.execute_contract(app_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap()
+            .data
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+/// Is used to instantiate the verifier contract with an open channel to cheqd,
+/// the IBC entry points are called directly as they are not supported by multi-test
+pub fn instantiate_verifier_with_cheqd_channel() -> MockDeps {
+    let mut deps = mock_dependencies();
+    let owner = deps.api.addr_make(OWNER_ADDR);
+    contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner, &[]),
+        InstantiateMsg {
+            max_presentation_len: MAX_PRESENTATION_LEN,
+            init_registrations: vec![],
+            cheqd_network: Some(CheqdNetwork::Testnet),
+        },
+    )
+    .unwrap();
+    contract::ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(CHEQD_CHANNEL_ID, IbcOrder::Unordered, "cheqd-resource-v3"),
+    )
+    .unwrap();
+    deps
+}
+
+pub fn cheqd_resource_req() -> ResourceReqPacket {
+    ResourceReqPacket {
+        resource_id: CHEQD_RESOURCE_ID.to_string(),
+        collection_id: CHEQD_COLLECTION_ID.to_string(),
+    }
+}
+
+/// Route requirements with the issuer keys fetched from cheqd
+pub fn cheqd_route_requirements(resource_req: &ResourceReqPacket) -> RouteVerificationRequirements {
+    RouteVerificationRequirements {
+        issuer_source_or_data: vec![IssuerSourceOrData {
+            source: Some(TrustRegistry::Cheqd),
+            data_or_location: to_json_binary(resource_req).unwrap(),
+        }],
+        presentation_required: None,
+    }
+}
+
+/// Registers `app_addr` with a single route fetching its issuer keys from cheqd
+pub fn register_cheqd_route(deps: &mut MockDeps, app_addr: &Addr, route_id: u64) -> Response {
+    contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(app_addr, &[]),
+        AvidaVerifierExecuteMsg::Register {
+            app_addr: app_addr.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id,
+                requirements: cheqd_route_requirements(&cheqd_resource_req()),
+            }],
+        }
+        .into(),
+    )
+    .unwrap()
+}
+
+/// The resource returned by cheqd for `resource_req`
+pub fn make_cheqd_resource(resource_req: &ResourceReqPacket, data: &[u8]) -> ResourceWithMetadata {
+    ResourceWithMetadata {
+        linked_resource: LinkedResource {
+            data: Binary::from(data),
+        },
+        linked_resource_metadata: LinkedResourceMetadata {
+            resource_collection_id: resource_req.collection_id.clone(),
+            resource_id: resource_req.resource_id.clone(),
+            resource_name: "issuer-keys".to_string(),
+            resource_version: "1.0.0".to_string(),
+            resource_type: "JSONWebKeys".to_string(),
+            resource_alternative_uri: vec![],
+            media_type: Some("application/json".to_string()),
+            created: "2024-01-01T00:00:00Z".to_string(),
+            checksum: Some(resource_checksum(data)),
+            previous_version_id: None,
+            next_version_id: None,
+        },
+    }
+}
+
+pub fn make_success_ack(resource: &ResourceWithMetadata) -> IbcAcknowledgement {
+    IbcAcknowledgement::new(StdAck::success(to_json_binary(resource).unwrap()).to_binary())
+}
+
+pub fn make_error_ack(err: &str) -> IbcAcknowledgement {
+    IbcAcknowledgement::new(StdAck::error(err).to_binary())
+}
@@ -3,7 +3,18 @@ mod contract_test;
 mod fixtures;
 mod types;
 mod verifier_exp_test;
+mod verifier_ibc_test;
+mod verifier_key_compromise_test;
 mod verifier_register_test;
+mod verifier_registry_test;
+mod verifier_revocation_delegate_test;
+mod verifier_revocation_root_test;
+mod verifier_revocation_test;
+mod verifier_routes_by_issuer_test;
+mod verifier_shared_revocation_list_test;
+mod verifier_status_list_test;
+mod verifier_suspension_test;
 mod verifier_test;
 mod verifier_update_test;
 mod verifier_verify_test;
+mod verifier_x5c_test;
@@ -0,0 +1,1558 @@
+use cosmwasm_std::testing::{
+    message_info, mock_dependencies, mock_env, mock_ibc_channel_close_init,
+    mock_ibc_channel_connect_ack, mock_ibc_packet_ack, mock_ibc_packet_timeout,
+};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Binary, CosmosMsg, Env, IbcBasicResponse, IbcMsg, IbcOrder,
+    Response, Timestamp,
+};
+
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk};
+
+use avida_cheqd::did_url::{CheqdNetwork, DidResourceUrl};
+use avida_cheqd::ibc::ResourceError;
+use avida_cheqd::time::parse_rfc3339;
+use avida_cheqd::types::{ResourceReqPacket, ResourceWithMetadata};
+use avida_common::types::{
+    AvidaVerifierExecuteMsg, IssuerSourceOrData, RegisterRouteRequest, RouteId,
+    RouteVerificationRequirements, TrustRegistry,
+};
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    contract,
+    errors::SdjwtVerifierError,
+    msg::{InstantiateMsg, QueryMsg},
+    types::{
+        CheqdChannelsResponse, CheqdKeySource, FetchFailure, InitRegistration, JwkInfo,
+        KeyResourceFormat, PendingResource, PendingRoute, ResolvedKeyVersion, ResourceFreshness,
+        ResourceVersion, RouteStatus, RouteStatusResponse, MAX_RESOURCE_AGE_DAYS,
+    },
+};
+
+use super::fixtures::{
+    cheqd_resource_req, cheqd_route_requirements, instantiate_verifier_with_cheqd_channel,
+    make_cheqd_resource, make_error_ack, make_success_ack, register_cheqd_route, MockDeps,
+    CHEQD_CHANNEL_ID, CHEQD_COLLECTION_ID, CHEQD_ISSUER_KEYS, CHEQD_RESOURCE_ID,
+};
+use crate::sdjwt::fixtures::{
+    FIRST_CALLER_APP_ADDR, FIRST_ROUTE_ID, MAX_PRESENTATION_LEN, OWNER_ADDR, SECOND_CALLER_APP_ADDR,
+};
+
+fn fetch_failures(deps: &MockDeps) -> Vec<FetchFailure> {
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    from_json(
+        contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetFetchFailures {
+                app_addr: app_addr.to_string(),
+                route_id: FIRST_ROUTE_ID,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn route_keys(deps: &MockDeps) -> Vec<String> {
+    app_route_keys(deps, &deps.api.addr_make(FIRST_CALLER_APP_ADDR))
+}
+
+fn app_route_keys(deps: &MockDeps, app_addr: &Addr) -> Vec<String> {
+    let keys: Option<Vec<String>> = from_json(
+        contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetRouteVerificationKey {
+                app_addr: app_addr.to_string(),
+                route_id: FIRST_ROUTE_ID,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    keys.unwrap_or_default()
+}
+
+fn route_status(deps: &MockDeps) -> RouteStatusResponse {
+    route_status_at(deps, mock_env())
+}
+
+fn route_status_at(deps: &MockDeps, env: Env) -> RouteStatusResponse {
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    from_json(
+        contract::query(
+            deps.as_ref(),
+            env,
+            QueryMsg::RouteStatus {
+                app_addr: app_addr.to_string(),
+                route_id: FIRST_ROUTE_ID,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn pending_fetches(deps: &MockDeps) -> Vec<(String, Vec<PendingRoute>)> {
+    from_json(
+        contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingFetches {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn assert_fetch_failed_event(res: &IbcBasicResponse, reason: &str) {
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == "resource_fetch_failed")
+        .unwrap();
+    assert!(event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "reason" && attr.value.contains(reason)));
+}
+
+#[test]
+fn resource_ack_stores_issuer_keys() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+
+    let res = register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+    assert_eq!(res.messages.len(), 1);
+    assert!(route_keys(&deps).is_empty());
+
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    contract::ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&resource),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(route_keys(&deps).len(), 1);
+    assert!(fetch_failures(&deps).is_empty());
+}
+
+#[test]
+fn timed_out_resource_fetch_is_recorded() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+
+    let res = contract::ibc_packet_timeout(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_timeout(CHEQD_CHANNEL_ID, &cheqd_resource_req()).unwrap(),
+    )
+    .unwrap();
+    assert_fetch_failed_event(&res, "timed out");
+
+    let failures = fetch_failures(&deps);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].resource_req, cheqd_resource_req());
+    assert_eq!(failures[0].resource, PendingResource::IssuerKeys);
+
+    // The pending request is removed, a late ack is ignored
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    let res = contract::ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&resource),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "ignored" && attr.value == "no_pending_request"));
+    assert!(route_keys(&deps).is_empty());
+}
+
+#[test]
+fn error_ack_and_unusable_resource_are_recorded() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+
+    let res = contract::ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_error_ack("resource not found"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_fetch_failed_event(&res, "resource not found");
+    assert_eq!(fetch_failures(&deps).len(), 1);
+
+    // Registering again re-sends the request and clears the failure
+    contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&app_addr, &[]),
+        AvidaVerifierExecuteMsg::Update {
+            app_addr: app_addr.to_string(),
+            route_id: FIRST_ROUTE_ID,
+            route_criteria: Some(cheqd_route_requirements(&cheqd_resource_req())),
+        }
+        .into(),
+    )
+    .unwrap();
+    assert!(fetch_failures(&deps).is_empty());
+
+    // A resource that is not a key set fails the fetch without failing the ack
+    let resource = make_cheqd_resource(&cheqd_resource_req(), b"not keys");
+    let res = contract::ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&resource),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_fetch_failed_event(&res, "IBC returned resource format unexpected");
+    assert_eq!(fetch_failures(&deps).len(), 1);
+    assert!(route_keys(&deps).is_empty());
+}
+
+fn time_out_fetch(deps: &mut MockDeps, env: Env) {
+    contract::ibc_packet_timeout(
+        deps.as_mut(),
+        env,
+        mock_ibc_packet_timeout(CHEQD_CHANNEL_ID, &cheqd_resource_req()).unwrap(),
+    )
+    .unwrap();
+}
+
+fn retry_pending_fetches(deps: &mut MockDeps, env: Env) -> Response {
+    let anyone = deps.api.addr_make("anyone");
+    contract::execute(
+        deps.as_mut(),
+        env,
+        message_info(&anyone, &[]),
+        SdjwtVerifierExecuteMsg::RetryPendingFetches { limit: None }.into(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn failed_fetches_are_retried_with_backoff() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+
+    let mut env = mock_env();
+    let mut delay = None;
+    loop {
+        time_out_fetch(&mut deps, env.clone());
+        let failure = fetch_failures(&deps).pop().unwrap();
+        let Some(next_retry) = failure.next_retry else {
+            break;
+        };
+
+        // The delay doubles after each failed attempt
+        let retry_delay = next_retry.seconds() - env.block.time.seconds();
+        if let Some(delay) = delay {
+            assert_eq!(retry_delay, 2 * delay);
+        }
+        delay = Some(retry_delay);
+
+        // Nothing to retry before the retry time
+        assert!(retry_pending_fetches(&mut deps, env.clone())
+            .messages
+            .is_empty());
+
+        env.block.time = next_retry;
+        assert_eq!(
+            retry_pending_fetches(&mut deps, env.clone()).messages.len(),
+            1
+        );
+
+        // The failure is kept while the retry is in flight
+        let failure = fetch_failures(&deps).pop().unwrap();
+        assert_eq!(failure.next_retry, None);
+    }
+
+    // The attempts are exhausted, the fetch is no longer retried
+    let failure = fetch_failures(&deps).pop().unwrap();
+    assert_eq!(failure.attempts, 5);
+    env.block.time = env.block.time.plus_days(365);
+    assert!(retry_pending_fetches(&mut deps, env.clone())
+        .messages
+        .is_empty());
+
+    // Updating the route re-sends the request with a new set of attempts
+    let res = contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        message_info(&app_addr, &[]),
+        AvidaVerifierExecuteMsg::Update {
+            app_addr: app_addr.to_string(),
+            route_id: FIRST_ROUTE_ID,
+            route_criteria: Some(cheqd_route_requirements(&cheqd_resource_req())),
+        }
+        .into(),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert!(fetch_failures(&deps).is_empty());
+
+    time_out_fetch(&mut deps, env);
+    assert_eq!(fetch_failures(&deps).pop().unwrap().attempts, 1);
+}
+
+#[test]
+fn route_status_follows_the_key_fetches() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+
+    contract::query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RouteStatus {
+            app_addr: app_addr.to_string(),
+            route_id: FIRST_ROUTE_ID,
+        },
+    )
+    .unwrap_err();
+
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+
+    let status = route_status(&deps);
+    assert_eq!(status.status, RouteStatus::PendingFetch);
+    assert_eq!(status.sources.len(), 1);
+    assert_eq!(status.sources[0].resource_req, cheqd_resource_req());
+    assert_eq!(status.sources[0].requested_at, mock_env().block.time);
+    assert_eq!(status.sources[0].fetched_at, None);
+    assert_eq!(
+        pending_fetches(&deps),
+        vec![(
+            cheqd_resource_req().to_string(),
+            vec![PendingRoute {
+                route_id: FIRST_ROUTE_ID,
+                app_addr: app_addr.to_string(),
+                resource: PendingResource::IssuerKeys,
+                source: None,
+            }]
+        )]
+    );
+
+    time_out_fetch(&mut deps, mock_env());
+    let status = route_status(&deps);
+    assert_eq!(status.status, RouteStatus::FetchFailed);
+    assert_eq!(status.failures.len(), 1);
+    assert!(pending_fetches(&deps).is_empty());
+
+    let mut env = mock_env();
+    env.block.time = status.failures[0].next_retry.unwrap();
+    retry_pending_fetches(&mut deps, env.clone());
+    assert_eq!(pending_fetches(&deps).len(), 1);
+
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    contract::ibc_packet_ack(
+        deps.as_mut(),
+        env.clone(),
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&resource),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let status = route_status(&deps);
+    assert_eq!(status.status, RouteStatus::Ready);
+    assert_eq!(status.sources[0].fetched_at, Some(env.block.time));
+    assert!(status.failures.is_empty());
+    assert!(pending_fetches(&deps).is_empty());
+}
+
+fn ack_issuer_keys(deps: &mut MockDeps, env: Env) {
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    contract::ibc_packet_ack(
+        deps.as_mut(),
+        env,
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&resource),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+}
+
+fn refresh_issuer_keys(deps: &mut MockDeps, env: Env) -> Result<Response, SdjwtVerifierError> {
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    let anyone = deps.api.addr_make("anyone");
+    contract::execute(
+        deps.as_mut(),
+        env,
+        message_info(&anyone, &[]),
+        SdjwtVerifierExecuteMsg::RefreshIssuerKeys {
+            app_addr: app_addr.to_string(),
+            route_id: FIRST_ROUTE_ID,
+        }
+        .into(),
+    )
+}
+
+#[test]
+fn issuer_keys_refresh_is_rate_limited() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+    ack_issuer_keys(&mut deps, mock_env());
+    assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+
+    // The route keys were just requested
+    let next_refresh = mock_env().block.time.plus_hours(1);
+    let err = refresh_issuer_keys(&mut deps, mock_env()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        SdjwtVerifierError::KeyRefreshTooSoon(next_refresh).to_string()
+    );
+
+    let mut env = mock_env();
+    env.block.time = next_refresh;
+    let res = refresh_issuer_keys(&mut deps, env.clone()).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // The previous keys are used while the refresh is in flight
+    let status = route_status(&deps);
+    assert_eq!(status.status, RouteStatus::Stale);
+    assert_eq!(status.sources[0].requested_at, next_refresh);
+    assert_eq!(status.sources[0].fetched_at, Some(mock_env().block.time));
+    assert_eq!(route_keys(&deps).len(), 1);
+    refresh_issuer_keys(&mut deps, env.clone()).unwrap_err();
+
+    env.block.time = env.block.time.plus_seconds(10);
+    ack_issuer_keys(&mut deps, env.clone());
+    let status = route_status(&deps);
+    assert_eq!(status.status, RouteStatus::Ready);
+    assert_eq!(status.sources[0].fetched_at, Some(env.block.time));
+}
+
+const NEXT_RESOURCE_ID: &str = "6a3a2d6e-0e36-4ac5-8f1e-2ad1c3f6b3d2";
+
+fn cheqd_key_source(version: ResourceVersion) -> CheqdKeySource {
+    CheqdKeySource {
+        resource_id: cheqd_resource_req().resource_id,
+        collection_id: cheqd_resource_req().collection_id,
+        version,
+        network: None,
+        format: KeyResourceFormat::Avida,
+        media_type: None,
+        resource_type: None,
+        resource_name: None,
+        max_age_days: None,
+        created_after: None,
+    }
+}
+
+fn register_cheqd_key_source(deps: &mut MockDeps, key_source: CheqdKeySource) {
+    register_cheqd_issuer_source(deps, to_json_binary(&key_source).unwrap()).unwrap();
+}
+
+fn register_cheqd_issuer_source(
+    deps: &mut MockDeps,
+    data_or_location: Binary,
+) -> Result<Response, SdjwtVerifierError> {
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&app_addr, &[]),
+        AvidaVerifierExecuteMsg::Register {
+            app_addr: app_addr.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id: FIRST_ROUTE_ID,
+                requirements: RouteVerificationRequirements {
+                    issuer_source_or_data: vec![IssuerSourceOrData {
+                        source: Some(TrustRegistry::Cheqd),
+                        data_or_location,
+                    }],
+                    presentation_required: None,
+                },
+            }],
+        }
+        .into(),
+    )
+}
+
+fn ack_resource(
+    deps: &mut MockDeps,
+    env: Env,
+    resource_req: &ResourceReqPacket,
+    resource: &ResourceWithMetadata,
+) -> IbcBasicResponse {
+    contract::ibc_packet_ack(
+        deps.as_mut(),
+        env,
+        mock_ibc_packet_ack(CHEQD_CHANNEL_ID, resource_req, make_success_ack(resource)).unwrap(),
+    )
+    .unwrap()
+}
+
+fn route_key_versions(deps: &MockDeps, height: Option<u64>) -> Vec<ResolvedKeyVersion> {
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    from_json(
+        contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RouteKeyVersions {
+                app_addr: app_addr.to_string(),
+                route_id: FIRST_ROUTE_ID,
+                height,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn latest_resource_version_is_followed() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(&mut deps, cheqd_key_source(ResourceVersion::Latest));
+
+    let next_req = ResourceReqPacket {
+        resource_id: NEXT_RESOURCE_ID.to_string(),
+        collection_id: cheqd_resource_req().collection_id,
+    };
+
+    // The first version points to a newer one, which is requested instead
+    let mut first_version = make_cheqd_resource(&cheqd_resource_req(), b"{}");
+    first_version.linked_resource_metadata.next_version_id = Some(NEXT_RESOURCE_ID.to_string());
+    let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &first_version);
+    assert_eq!(res.messages.len(), 1);
+    assert!(route_keys(&deps).is_empty());
+    assert_eq!(route_status(&deps).status, RouteStatus::PendingFetch);
+
+    let pending = pending_fetches(&deps);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].0, next_req.to_string());
+    assert_eq!(pending[0].1[0].source, Some(cheqd_resource_req()));
+
+    let mut env = mock_env();
+    env.block.height += 1;
+    let mut latest_version = make_cheqd_resource(&next_req, CHEQD_ISSUER_KEYS.as_bytes());
+    latest_version.linked_resource_metadata.resource_version = "2.0.0".to_string();
+    latest_version.linked_resource_metadata.previous_version_id =
+        Some(CHEQD_RESOURCE_ID.to_string());
+    ack_resource(&mut deps, env.clone(), &next_req, &latest_version);
+
+    assert_eq!(route_keys(&deps).len(), 1);
+    assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+    assert!(pending_fetches(&deps).is_empty());
+
+    // The resolved version is recorded for the source, from the next height on
+    let versions = route_key_versions(&deps, None);
+    assert_eq!(
+        versions,
+        vec![ResolvedKeyVersion {
+            source: cheqd_resource_req(),
+            metadata: latest_version.linked_resource_metadata,
+        }]
+    );
+    assert!(route_key_versions(&deps, Some(env.block.height)).is_empty());
+    assert_eq!(
+        route_key_versions(&deps, Some(env.block.height + 1)),
+        versions
+    );
+}
+
+#[test]
+fn pinned_resource_versions_are_enforced() {
+    for (version, expected_err) in [
+        (
+            ResourceVersion::Version("2.0.0".to_string()),
+            SdjwtVerifierError::ResourceVersionMismatch("2.0.0".to_string(), "1.0.0".to_string()),
+        ),
+        (
+            ResourceVersion::Checksum("00".repeat(32)),
+            SdjwtVerifierError::ResourceChecksumMismatch("00".repeat(32)),
+        ),
+    ] {
+        let mut deps = instantiate_verifier_with_cheqd_channel();
+        register_cheqd_key_source(&mut deps, cheqd_key_source(version));
+
+        let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+        let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+        assert_fetch_failed_event(&res, &expected_err.to_string());
+        assert!(route_keys(&deps).is_empty());
+        assert_eq!(route_status(&deps).status, RouteStatus::FetchFailed);
+    }
+
+    // The pinned version and checksum are accepted
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    for version in [
+        ResourceVersion::Version("1.0.0".to_string()),
+        ResourceVersion::Checksum(
+            resource
+                .linked_resource_metadata
+                .checksum
+                .clone()
+                .unwrap()
+                .to_uppercase(),
+        ),
+    ] {
+        let mut deps = instantiate_verifier_with_cheqd_channel();
+        register_cheqd_key_source(&mut deps, cheqd_key_source(version));
+        ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+        assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+    }
+}
+
+#[test]
+fn resources_are_checked_against_checksum_and_requirements() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            media_type: Some("application/json".to_string()),
+            resource_type: Some("JSONWebKeys".to_string()),
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+
+    // The data does not match the checksum of the resource
+    let mut resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    let expected = resource.linked_resource_metadata.checksum.clone().unwrap();
+    resource.linked_resource.data = br#"{"issuer":{}}"#.into();
+    let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(
+        &res,
+        &ResourceError::ChecksumMismatch {
+            expected,
+            actual: make_cheqd_resource(&cheqd_resource_req(), br#"{"issuer":{}}"#)
+                .linked_resource_metadata
+                .checksum
+                .unwrap(),
+        }
+        .to_string(),
+    );
+    assert_eq!(route_status(&deps).status, RouteStatus::FetchFailed);
+
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            media_type: Some("application/jwk-set+json".to_string()),
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(
+        &res,
+        &ResourceError::UnexpectedMediaType {
+            expected: "application/jwk-set+json".to_string(),
+            actual: Some("application/json".to_string()),
+        }
+        .to_string(),
+    );
+
+    // The resource matches the requirements of the source
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            media_type: Some("application/json".to_string()),
+            resource_type: Some("JSONWebKeys".to_string()),
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+    ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+}
+
+#[test]
+fn resource_created_time_is_parsed() {
+    for (time, seconds, nanos) in [
+        ("1970-01-01T00:00:00Z", 0, 0),
+        ("2024-01-01T00:00:00Z", 1_704_067_200, 0),
+        ("2024-02-29T12:30:15.25Z", 1_709_209_815, 250_000_000),
+        ("2024-01-01T01:00:00+01:00", 1_704_067_200, 0),
+        (
+            "2023-12-31T19:30:00.123456789-04:30",
+            1_704_067_200,
+            123_456_789,
+        ),
+    ] {
+        assert_eq!(
+            parse_rfc3339(time).unwrap(),
+            Timestamp::from_seconds(seconds).plus_nanos(nanos),
+            "{time}"
+        );
+    }
+
+    for time in [
+        "",
+        "2024-01-01",
+        "2024-01-01T00:00:00",
+        "2023-02-29T00:00:00Z",
+        "2024-13-01T00:00:00Z",
+        "2024-01-01T24:00:00Z",
+        "2024-01-01T00:00:00.Z",
+        "2024-01-01T00:00:00+0100",
+        "1969-12-31T23:59:59Z",
+        // Before the epoch, the year is shifted in January and February
+        "0000-01-01T00:00:00Z",
+        "0000-02-29T00:00:00Z",
+    ] {
+        assert_eq!(
+            parse_rfc3339(time),
+            Err(ResourceError::InvalidTime(time.to_string()))
+        );
+    }
+}
+
+#[test]
+fn key_resources_must_be_fresh() {
+    // The fixture resources are created on 2024-01-01
+    let created = parse_rfc3339("2024-01-01T00:00:00Z").unwrap();
+    let mut env = mock_env();
+    env.block.time = created.plus_days(30);
+
+    // Resources created before `createdAfter` fail the fetch
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            created_after: Some(created),
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    let res = ack_resource(&mut deps, env.clone(), &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(
+        &res,
+        &SdjwtVerifierError::ResourceNotFresh("2024-01-01T00:00:00Z".to_string()).to_string(),
+    );
+    assert!(route_keys(&deps).is_empty());
+    assert_eq!(route_status(&deps).status, RouteStatus::FetchFailed);
+
+    // Resources older than `maxAgeDays` are accepted until they are too old
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            max_age_days: Some(30),
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+    ack_resource(&mut deps, env.clone(), &cheqd_resource_req(), &resource);
+    assert_eq!(route_keys(&deps).len(), 1);
+
+    assert_eq!(
+        route_status_at(&deps, env.clone()).status,
+        RouteStatus::Ready
+    );
+
+    // Once too old, the keys are flagged for a refresh
+    env.block.time = env.block.time.plus_seconds(1);
+    assert_eq!(
+        route_status_at(&deps, env.clone()).status,
+        RouteStatus::Stale
+    );
+
+    // and the refreshed resource is rejected
+    refresh_issuer_keys(&mut deps, env.clone()).unwrap();
+    let res = ack_resource(&mut deps, env, &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(
+        &res,
+        &SdjwtVerifierError::ResourceNotFresh("2024-01-01T00:00:00Z".to_string()).to_string(),
+    );
+
+    // The maximum age is bounded on registration
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let err = register_cheqd_issuer_source(
+        &mut deps,
+        to_json_binary(&CheqdKeySource {
+            max_age_days: Some(MAX_RESOURCE_AGE_DAYS + 1),
+            ..cheqd_key_source(ResourceVersion::Requested)
+        })
+        .unwrap(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, SdjwtVerifierError::ResourceMaxAgeTooLarge(_)));
+
+    // and an age past the largest timestamp never expires
+    let freshness = ResourceFreshness {
+        max_age_days: Some(u64::MAX),
+        created_after: None,
+    };
+    assert!(freshness.is_fresh(created, Timestamp::from_nanos(u64::MAX)));
+}
+
+#[test]
+fn did_resource_urls_are_parsed() {
+    let url: DidResourceUrl =
+        format!("did:cheqd:testnet:{CHEQD_COLLECTION_ID}/resources/{CHEQD_RESOURCE_ID}")
+            .parse()
+            .unwrap();
+    assert_eq!(url.network, CheqdNetwork::Testnet);
+    assert_eq!(url.resource_req().unwrap(), cheqd_resource_req());
+
+    // DIDs without a namespace are on mainnet
+    let url: DidResourceUrl = format!(
+        "did:cheqd:{CHEQD_COLLECTION_ID}?resourceId={CHEQD_RESOURCE_ID}&resourceType=JSONWebKeys"
+    )
+    .parse()
+    .unwrap();
+    assert_eq!(url.network, CheqdNetwork::Mainnet);
+    assert_eq!(url.resource_req().unwrap(), cheqd_resource_req());
+    assert_eq!(url.requirements().resource_type.unwrap(), "JSONWebKeys");
+
+    // Query URLs by name are parsed, but cannot be fetched without a resource id
+    let url: DidResourceUrl = format!(
+        "did:cheqd:testnet:{CHEQD_COLLECTION_ID}?resourceName=issuer%20keys&resourceType=JSONWebKeys"
+    )
+    .parse()
+    .unwrap();
+    assert_eq!(url.resource_name.as_deref(), Some("issuer keys"));
+    assert_eq!(url.to_string().parse::<DidResourceUrl>().unwrap(), url);
+    assert_eq!(
+        url.resource_req(),
+        Err(ResourceError::ResourceIdMissing(url.to_string()))
+    );
+
+    assert_eq!(
+        format!("did:cheqd:devnet:{CHEQD_COLLECTION_ID}/resources/{CHEQD_RESOURCE_ID}")
+            .parse::<DidResourceUrl>(),
+        Err(ResourceError::UnknownNetwork("devnet".to_string()))
+    );
+    for url in [
+        format!("did:web:example.com/resources/{CHEQD_RESOURCE_ID}"),
+        format!("did:cheqd:testnet:{CHEQD_COLLECTION_ID}"),
+        format!("did:cheqd:testnet:{CHEQD_COLLECTION_ID}/keys/{CHEQD_RESOURCE_ID}"),
+        format!("did:cheqd:testnet:{CHEQD_COLLECTION_ID}#key-1"),
+        format!("did:cheqd:testnet:{CHEQD_COLLECTION_ID}?resourceVersionTime=2024-01-01"),
+        format!(
+            "did:cheqd:testnet:{CHEQD_COLLECTION_ID}/resources/{CHEQD_RESOURCE_ID}?resourceId=other"
+        ),
+    ] {
+        assert!(matches!(
+            url.parse::<DidResourceUrl>(),
+            Err(ResourceError::InvalidDidUrl(_))
+        ));
+    }
+}
+
+#[test]
+fn cheqd_sources_are_registered_by_did_url() {
+    let url = format!(
+        "did:cheqd:testnet:{CHEQD_COLLECTION_ID}/resources/{CHEQD_RESOURCE_ID}?resourceType=JSONWebKeys"
+    );
+
+    // The URL is accepted as is or as a JSON string
+    for data in [Binary::from(url.as_bytes()), to_json_binary(&url).unwrap()] {
+        let mut deps = instantiate_verifier_with_cheqd_channel();
+        let res = register_cheqd_issuer_source(&mut deps, data).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let status = route_status(&deps);
+        assert_eq!(status.sources[0].resource_req, cheqd_resource_req());
+        assert_eq!(
+            status.sources[0].requirements.resource_type.as_deref(),
+            Some("JSONWebKeys")
+        );
+
+        let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+        ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+        assert_eq!(route_keys(&deps).len(), 1);
+    }
+
+    // The verifier is connected to testnet
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let err = register_cheqd_issuer_source(
+        &mut deps,
+        Binary::from(url.replace("testnet:", "mainnet:").as_bytes()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        SdjwtVerifierError::CheqdNetworkMismatch("testnet".to_string(), "mainnet".to_string())
+            .to_string()
+    );
+
+    // Only resources with an id can be fetched
+    let err = register_cheqd_issuer_source(
+        &mut deps,
+        Binary::from(
+            format!("did:cheqd:testnet:{CHEQD_COLLECTION_ID}?resourceName=issuer-keys").as_bytes(),
+        ),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        SdjwtVerifierError::ResourceError(ResourceError::ResourceIdMissing(_))
+    ));
+}
+
+const CHEQD_ISSUER_X: &str = "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo";
+
+fn jwk_x(jwk: &Jwk) -> &str {
+    match &jwk.algorithm {
+        AlgorithmParameters::OctetKeyPair(params) => &params.x,
+        _ => panic!("not an OKP key"),
+    }
+}
+
+fn did_document(did: &str) -> String {
+    format!(
+        r##"{{
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": "{did}",
+            "controller": ["{did}"],
+            "verificationMethod": [
+                {{
+                    "id": "{did}#key-1",
+                    "type": "Ed25519VerificationKey2020",
+                    "controller": "{did}",
+                    "publicKeyMultibase": "z6MktwupdmLXVVqTzCw4i46r4uGyosGXRnR3XjN4Zq7oMMsw"
+                }},
+                {{
+                    "id": "{did}#key-2",
+                    "type": "JsonWebKey2020",
+                    "controller": "{did}",
+                    "publicKeyJwk": {{
+                        "kty": "OKP",
+                        "crv": "Ed25519",
+                        "x": "{CHEQD_ISSUER_X}"
+                    }}
+                }},
+                {{
+                    "id": "{did}#key-3",
+                    "type": "Ed25519VerificationKey2018",
+                    "controller": "{did}",
+                    "publicKeyBase58": "FVen3X669xLzsi6N2V91DoiyzHzg1uAgqiT8jZ9nS96Z"
+                }},
+                {{
+                    "id": "{did}#key-ec",
+                    "type": "JsonWebKey2020",
+                    "controller": "{did}",
+                    "publicKeyJwk": {EC_JWK}
+                }}
+            ],
+            "authentication": ["{did}#key-3"],
+            "assertionMethod": [{BLS_METHOD}, "#key-1", "#key-ec", "{did}#key-2"]
+        }}"##,
+        BLS_METHOD = bls_verification_method(did),
+    )
+}
+
+const EC_JWK: &str = r#"{"kty":"EC","crv":"P-256","x":"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU","y":"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"}"#;
+
+fn bls_verification_method(did: &str) -> String {
+    format!(
+        r#"{{"id":"{did}#key-bls","type":"Bls12381G2Key2020","controller":"{did}","publicKeyBase58":"25EEkQtcLKsEzQ6JTo9cg4W7NHpaurn4Wg6LaNPFq6JQXnrP91SDviUz7KrJVMJd76CtAZFsRLYzvgX2JGxo2ccUHtuHk7ELCWwrkBDfrXCFVfqJKDootee9iVaF6NpdJtBE"}}"#
+    )
+}
+
+#[test]
+fn did_document_assertion_keys_are_stored() {
+    let did = format!("did:cheqd:testnet:{CHEQD_COLLECTION_ID}");
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            format: KeyResourceFormat::DidDocument,
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+
+    let resource = make_cheqd_resource(&cheqd_resource_req(), did_document(&did).as_bytes());
+    ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+
+    // The first supported assertion method key is the key of the DID, others are skipped
+    let kids: Vec<String> = route_keys(&deps)
+        .iter()
+        .map(|key| from_json::<Jwk>(key.as_bytes()).unwrap())
+        .inspect(|jwk| assert_eq!(jwk_x(jwk), CHEQD_ISSUER_X))
+        .map(|jwk| jwk.common.key_id.unwrap())
+        .collect();
+    assert_eq!(kids, vec![format!("{did}#key-1")]);
+    // Only the assertion method keys are kept by key id
+    let mut source_kids = route_source_key_ids(&deps).remove(0).1;
+    source_kids.sort();
+    assert_eq!(
+        source_kids,
+        vec![did.clone(), format!("{did}#key-1"), format!("{did}#key-2")]
+    );
+    assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+
+    // A document without a supported assertion method key is invalid
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            format: KeyResourceFormat::DidDocument,
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+    let document = format!(
+        r##"{{"id":"{did}","verificationMethod":[{{"id":"{did}#key-ec","type":"JsonWebKey2020","controller":"{did}","publicKeyJwk":{EC_JWK}}}],"assertionMethod":[{},"#key-ec"]}}"##,
+        bls_verification_method(&did)
+    );
+    let resource = make_cheqd_resource(&cheqd_resource_req(), document.as_bytes());
+    let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(
+        &res,
+        &SdjwtVerifierError::DidDocumentInvalid("no supported assertion method".to_string())
+            .to_string(),
+    );
+    assert!(route_keys(&deps).is_empty());
+
+    // The document must be the one of the DID the resource is linked to
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_cheqd_key_source(
+        &mut deps,
+        CheqdKeySource {
+            format: KeyResourceFormat::DidDocument,
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+    let other_did = "did:cheqd:testnet:Ps1ysXP2Ae6GBfxNhNQNKN";
+    let resource = make_cheqd_resource(&cheqd_resource_req(), did_document(other_did).as_bytes());
+    let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(
+        &res,
+        &SdjwtVerifierError::DidDocumentInvalid(format!(
+            "{other_did} is not the DID of the resource"
+        ))
+        .to_string(),
+    );
+    assert!(route_keys(&deps).is_empty());
+}
+
+/// The issuer keys of the route, by their `kid`
+fn route_key_ids(deps: &MockDeps) -> Vec<Option<String>> {
+    let mut kids: Vec<Option<String>> = route_keys(deps)
+        .iter()
+        .map(|key| from_json::<Jwk>(key.as_bytes()).unwrap().common.key_id)
+        .collect();
+    kids.sort();
+    kids
+}
+
+fn register_key_format(deps: &mut MockDeps, format: KeyResourceFormat) {
+    register_cheqd_key_source(
+        deps,
+        CheqdKeySource {
+            format,
+            ..cheqd_key_source(ResourceVersion::Requested)
+        },
+    );
+}
+
+#[test]
+fn key_resource_formats_are_parsed() {
+    const ISSUER: &str = "https://issuer.example";
+    let ed25519_jwk = |kid: &str| {
+        format!(r#"{{"kty":"OKP","crv":"Ed25519","kid":"{kid}","x":"{CHEQD_ISSUER_X}"}}"#)
+    };
+    // Keys that are not Ed25519 are ignored
+    let jwks = format!(
+        r#"{{"keys":[{},{{"kty":"EC","crv":"P-256","kid":"ec","x":"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU","y":"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"}},{}]}}"#,
+        ed25519_jwk("key-1"),
+        ed25519_jwk("key-2")
+    );
+
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_key_format(
+        &mut deps,
+        KeyResourceFormat::Jwks {
+            issuer: ISSUER.to_string(),
+        },
+    );
+    let resource = make_cheqd_resource(&cheqd_resource_req(), jwks.as_bytes());
+    ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    // The first key is the key of the issuer, the others are only selected by `kid`
+    assert_eq!(route_key_ids(&deps), vec![Some("key-1".to_string())]);
+    let mut source_kids = route_source_key_ids(&deps).remove(0).1;
+    source_kids.sort();
+    assert_eq!(
+        source_kids,
+        vec![
+            ISSUER.to_string(),
+            format!("{ISSUER}#key-1"),
+            format!("{ISSUER}#key-2")
+        ]
+    );
+    // The keys selected by `kid` are not issuers of the route
+    let routes_by_issuer = |iss: &str| -> Vec<(String, RouteId)> {
+        from_json(
+            contract::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RoutesByIssuer {
+                    iss: iss.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        routes_by_issuer(ISSUER),
+        vec![(
+            deps.api.addr_make(FIRST_CALLER_APP_ADDR).to_string(),
+            FIRST_ROUTE_ID
+        )]
+    );
+    assert!(routes_by_issuer(&format!("{ISSUER}#key-2")).is_empty());
+
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_key_format(
+        &mut deps,
+        KeyResourceFormat::Jwk {
+            issuer: ISSUER.to_string(),
+        },
+    );
+    let resource = make_cheqd_resource(&cheqd_resource_req(), ed25519_jwk("key-1").as_bytes());
+    ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_eq!(route_key_ids(&deps), vec![Some("key-1".to_string())]);
+
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_key_format(&mut deps, KeyResourceFormat::JwtVcIssuer);
+    let metadata = format!(r#"{{"issuer":"{ISSUER}","jwks":{jwks}}}"#);
+    let resource = make_cheqd_resource(&cheqd_resource_req(), metadata.as_bytes());
+    ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_eq!(route_key_ids(&deps).len(), 1);
+    assert_eq!(route_source_key_ids(&deps).remove(0).1.len(), 3);
+
+    // Remote key sets cannot be fetched
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_key_format(&mut deps, KeyResourceFormat::JwtVcIssuer);
+    let metadata = format!(r#"{{"issuer":"{ISSUER}","jwks_uri":"{ISSUER}/jwks.json"}}"#);
+    let resource = make_cheqd_resource(&cheqd_resource_req(), metadata.as_bytes());
+    let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(&res, "jwks_uri is not supported");
+    assert!(route_keys(&deps).is_empty());
+
+    // The data must be in the declared format
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    register_key_format(
+        &mut deps,
+        KeyResourceFormat::Jwks {
+            issuer: ISSUER.to_string(),
+        },
+    );
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    let res = ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_fetch_failed_event(&res, "IBC returned resource format unexpected");
+}
+
+fn route_source_key_ids(deps: &MockDeps) -> Vec<(String, Vec<String>)> {
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    from_json(
+        contract::query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RouteKeyIds {
+                app_addr: app_addr.to_string(),
+                route_id: FIRST_ROUTE_ID,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn issuer_keys_are_merged_per_source() {
+    let ed25519_jwk = format!(r#"{{"kty":"OKP","crv":"Ed25519","x":"{CHEQD_ISSUER_X}"}}"#);
+    let jwk_source = |resource_id: &str, issuer: &str| IssuerSourceOrData {
+        source: Some(TrustRegistry::Cheqd),
+        data_or_location: to_json_binary(&CheqdKeySource {
+            resource_id: resource_id.to_string(),
+            format: KeyResourceFormat::Jwk {
+                issuer: issuer.to_string(),
+            },
+            ..cheqd_key_source(ResourceVersion::Requested)
+        })
+        .unwrap(),
+    };
+    let next_resource_req = ResourceReqPacket {
+        resource_id: NEXT_RESOURCE_ID.to_string(),
+        ..cheqd_resource_req()
+    };
+
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&app_addr, &[]),
+        AvidaVerifierExecuteMsg::Register {
+            app_addr: app_addr.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id: FIRST_ROUTE_ID,
+                requirements: RouteVerificationRequirements {
+                    issuer_source_or_data: vec![
+                        IssuerSourceOrData {
+                            source: None,
+                            data_or_location: to_json_binary(&JwkInfo {
+                                jwk: Binary::from(ed25519_jwk.as_bytes()),
+                                issuer: "inline-issuer".to_string(),
+                            })
+                            .unwrap(),
+                        },
+                        jwk_source(CHEQD_RESOURCE_ID, "first-issuer"),
+                        jwk_source(NEXT_RESOURCE_ID, "next-issuer"),
+                    ],
+                    presentation_required: None,
+                },
+            }],
+        }
+        .into(),
+    )
+    .unwrap();
+    assert_eq!(route_keys(&deps).len(), 1);
+    assert_eq!(route_status(&deps).status, RouteStatus::PendingFetch);
+
+    // The directly provided keys are kept when a source resolves
+    let resource = make_cheqd_resource(&cheqd_resource_req(), ed25519_jwk.as_bytes());
+    ack_resource(&mut deps, mock_env(), &cheqd_resource_req(), &resource);
+    assert_eq!(route_keys(&deps).len(), 2);
+    assert_eq!(route_status(&deps).status, RouteStatus::PendingFetch);
+
+    // The route is ready once every source has resolved
+    let resource = make_cheqd_resource(&next_resource_req, ed25519_jwk.as_bytes());
+    ack_resource(&mut deps, mock_env(), &next_resource_req, &resource);
+    assert_eq!(route_keys(&deps).len(), 3);
+    assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+
+    // By source, in key order
+    assert_eq!(
+        route_source_key_ids(&deps),
+        vec![
+            (
+                next_resource_req.to_string(),
+                vec!["next-issuer".to_string()]
+            ),
+            (
+                cheqd_resource_req().to_string(),
+                vec!["first-issuer".to_string()]
+            ),
+            ("inline".to_string(), vec!["inline-issuer".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn resource_fetches_are_shared_across_routes() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let first_app = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    let second_app = deps.api.addr_make(SECOND_CALLER_APP_ADDR);
+
+    // The second route subscribes to the request in flight
+    let res = register_cheqd_route(&mut deps, &first_app, FIRST_ROUTE_ID);
+    assert_eq!(res.messages.len(), 1);
+    let res = register_cheqd_route(&mut deps, &second_app, FIRST_ROUTE_ID);
+    assert!(res.messages.is_empty());
+    let pending = pending_fetches(&deps);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].1.len(), 2);
+
+    // The ack updates every subscribed route
+    ack_issuer_keys(&mut deps, mock_env());
+    assert_eq!(app_route_keys(&deps, &first_app).len(), 1);
+    assert_eq!(app_route_keys(&deps, &second_app).len(), 1);
+    assert!(pending_fetches(&deps).is_empty());
+
+    // The cached resource is served without another IBC packet
+    let third_app = deps.api.addr_make("third-app");
+    let res = register_cheqd_route(&mut deps, &third_app, FIRST_ROUTE_ID);
+    assert!(res.messages.is_empty());
+    assert_eq!(app_route_keys(&deps, &third_app).len(), 1);
+    assert!(pending_fetches(&deps).is_empty());
+
+    // except to the sources following the latest version, which may have changed
+    let res = contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&first_app, &[]),
+        AvidaVerifierExecuteMsg::Update {
+            app_addr: first_app.to_string(),
+            route_id: FIRST_ROUTE_ID,
+            route_criteria: Some(RouteVerificationRequirements {
+                issuer_source_or_data: vec![IssuerSourceOrData {
+                    source: Some(TrustRegistry::Cheqd),
+                    data_or_location: to_json_binary(&cheqd_key_source(ResourceVersion::Latest))
+                        .unwrap(),
+                }],
+                presentation_required: None,
+            }),
+        }
+        .into(),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(route_status(&deps).status, RouteStatus::PendingFetch);
+}
+
+#[test]
+fn fetches_are_queued_until_the_channel_is_connected() {
+    let mut deps = mock_dependencies();
+    let owner = deps.api.addr_make(OWNER_ADDR);
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    let res = contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner, &[]),
+        InstantiateMsg {
+            max_presentation_len: MAX_PRESENTATION_LEN,
+            init_registrations: vec![InitRegistration {
+                app_addr: app_addr.to_string(),
+                app_admin: app_addr.to_string(),
+                routes: vec![RegisterRouteRequest {
+                    route_id: FIRST_ROUTE_ID,
+                    requirements: cheqd_route_requirements(&cheqd_resource_req()),
+                }],
+            }],
+            cheqd_network: None,
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(route_status(&deps).status, RouteStatus::PendingFetch);
+    assert_eq!(pending_fetches(&deps).len(), 1);
+
+    // Nothing can be sent before the channel is connected
+    assert!(retry_pending_fetches(&mut deps, mock_env())
+        .messages
+        .is_empty());
+
+    let res = contract::ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(CHEQD_CHANNEL_ID, IbcOrder::Unordered, "cheqd-resource-v3"),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    // The queued fetch is sent once
+    assert!(retry_pending_fetches(&mut deps, mock_env())
+        .messages
+        .is_empty());
+
+    ack_issuer_keys(&mut deps, mock_env());
+    assert_eq!(route_keys(&deps).len(), 1);
+    assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+}
+
+const STANDBY_CHANNEL_ID: &str = "channel-1";
+
+fn cheqd_channels(deps: &MockDeps) -> CheqdChannelsResponse {
+    from_json(contract::query(deps.as_ref(), mock_env(), QueryMsg::CheqdChannels {}).unwrap())
+        .unwrap()
+}
+
+fn set_cheqd_channel(deps: &mut MockDeps, sender: &str) -> Result<Response, SdjwtVerifierError> {
+    switch_cheqd_channel(deps, sender, STANDBY_CHANNEL_ID)
+}
+
+fn switch_cheqd_channel(
+    deps: &mut MockDeps,
+    sender: &str,
+    channel_id: &str,
+) -> Result<Response, SdjwtVerifierError> {
+    let sender = deps.api.addr_make(sender);
+    contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        SdjwtVerifierExecuteMsg::SetCheqdChannel {
+            channel_id: channel_id.to_string(),
+        }
+        .into(),
+    )
+}
+
+fn packet_channel(res: &Response) -> &str {
+    match &res.messages[0].msg {
+        CosmosMsg::Ibc(IbcMsg::SendPacket { channel_id, .. }) => channel_id,
+        msg => panic!("unexpected message {msg:?}"),
+    }
+}
+
+#[test]
+fn owner_can_replace_the_cheqd_channel() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+
+    // A later channel is kept on standby
+    let res = contract::ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(STANDBY_CHANNEL_ID, IbcOrder::Unordered, "cheqd-resource-v3"),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "active" && attr.value == "false"));
+    let channels = cheqd_channels(&deps);
+    assert_eq!(channels.active.as_deref(), Some(CHEQD_CHANNEL_ID));
+    assert_eq!(channels.channels.len(), 2);
+
+    let err = set_cheqd_channel(&mut deps, FIRST_CALLER_APP_ADDR).unwrap_err();
+    assert!(matches!(err, SdjwtVerifierError::UnauthorisedCaller));
+
+    // The fetch in flight on the previous channel is failed
+    set_cheqd_channel(&mut deps, OWNER_ADDR).unwrap();
+    assert_eq!(
+        cheqd_channels(&deps).active.as_deref(),
+        Some(STANDBY_CHANNEL_ID)
+    );
+    assert!(pending_fetches(&deps).is_empty());
+    let failure = fetch_failures(&deps).pop().unwrap();
+    assert!(failure.reason.contains("IBC channel replaced"));
+    assert_eq!(route_status(&deps).status, RouteStatus::FetchFailed);
+
+    let err = set_cheqd_channel(&mut deps, OWNER_ADDR).unwrap_err();
+    assert!(matches!(err, SdjwtVerifierError::ChannelAlreadyActive(_)));
+
+    // A late ack on the previous channel is ignored
+    let res = contract::ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&make_cheqd_resource(
+                &cheqd_resource_req(),
+                CHEQD_ISSUER_KEYS.as_bytes(),
+            )),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "ignored"));
+    assert!(route_keys(&deps).is_empty());
+
+    // The fetch is retried on the new channel
+    let mut env = mock_env();
+    env.block.time = failure.next_retry.unwrap();
+    let res = retry_pending_fetches(&mut deps, env);
+    assert_eq!(packet_channel(&res), STANDBY_CHANNEL_ID);
+}
+
+#[test]
+fn late_ack_on_a_reactivated_channel_is_ignored() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+    contract::ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(STANDBY_CHANNEL_ID, IbcOrder::Unordered, "cheqd-resource-v3"),
+    )
+    .unwrap();
+
+    // The fetch in flight is failed by the switch and not resent when switching back
+    set_cheqd_channel(&mut deps, OWNER_ADDR).unwrap();
+    let res = switch_cheqd_channel(&mut deps, OWNER_ADDR, CHEQD_CHANNEL_ID).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(pending_fetches(&deps).is_empty());
+
+    // Its late ack arrives on the active channel without a pending request
+    let res = contract::ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            CHEQD_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&make_cheqd_resource(
+                &cheqd_resource_req(),
+                CHEQD_ISSUER_KEYS.as_bytes(),
+            )),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "ignored" && attr.value == "no_pending_request"));
+    assert!(route_keys(&deps).is_empty());
+    assert_eq!(route_status(&deps).status, RouteStatus::FetchFailed);
+}
+
+#[test]
+fn closed_channel_fails_the_fetches_in_flight() {
+    let mut deps = instantiate_verifier_with_cheqd_channel();
+    let app_addr = deps.api.addr_make(FIRST_CALLER_APP_ADDR);
+    register_cheqd_route(&mut deps, &app_addr, FIRST_ROUTE_ID);
+
+    let res = contract::ibc_channel_close(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_close_init(CHEQD_CHANNEL_ID, IbcOrder::Unordered, "cheqd-resource-v3"),
+    )
+    .unwrap();
+    assert_fetch_failed_event(&res, "IBC channel closed");
+    assert!(pending_fetches(&deps).is_empty());
+
+    let channels = cheqd_channels(&deps);
+    assert_eq!(channels.active, None);
+    assert_eq!(channels.channels[0].closed_at, Some(mock_env().block.time));
+
+    // A newly connected channel is not used until the owner switches to it,
+    // the retried fetch is queued meanwhile
+    contract::ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(STANDBY_CHANNEL_ID, IbcOrder::Unordered, "cheqd-resource-v3"),
+    )
+    .unwrap();
+    let mut env = mock_env();
+    env.block.time = fetch_failures(&deps).pop().unwrap().next_retry.unwrap();
+    assert!(retry_pending_fetches(&mut deps, env).messages.is_empty());
+
+    let res = set_cheqd_channel(&mut deps, OWNER_ADDR).unwrap();
+    assert_eq!(packet_channel(&res), STANDBY_CHANNEL_ID);
+    let resource = make_cheqd_resource(&cheqd_resource_req(), CHEQD_ISSUER_KEYS.as_bytes());
+    contract::ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            STANDBY_CHANNEL_ID,
+            &cheqd_resource_req(),
+            make_success_ack(&resource),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(route_status(&deps).status, RouteStatus::Ready);
+}
@@ -0,0 +1,191 @@
+use cosmwasm_std::{Addr, Timestamp};
+use cw_multi_test::{App, Executor};
+use serde_json::Value;
+
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::QueryMsg,
+    types::{KeyCompromise, VerifyResult},
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims, claims_with_revocation_idx, issuer_key_thumbprint, make_presentation,
+    PresentationVerificationType, FIRST_CALLER_APP_ADDR, FIRST_ROUTE_ID, OWNER_ADDR,
+};
+
+const CUTOFF: u64 = 1_700_000_000;
+
+fn issued_at(mut claims: Value, iat: u64) -> Value {
+    claims["iat"] = iat.into();
+    claims
+}
+
+fn verify(app: &mut App, contract_addr: &Addr, claims: Value) -> VerifyResult {
+    let first_caller_app_addr = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+    let presentation = make_presentation(claims, PresentationVerificationType::Success);
+    verify_presentation(
+        app,
+        contract_addr,
+        &first_caller_app_addr,
+        FIRST_ROUTE_ID,
+        &presentation,
+        None,
+    )
+}
+
+fn set_key_compromised(
+    app: &mut App,
+    contract_addr: &Addr,
+    sender: &Addr,
+    app_addr: Option<String>,
+    allowed_idx: Option<Vec<u64>>,
+) {
+    app.execute_contract(
+        sender.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::SetKeyCompromised {
+            app_addr,
+            key_thumbprint: issuer_key_thumbprint(),
+            cutoff: Timestamp::from_seconds(CUTOFF),
+            allowed_idx,
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn assert_key_compromised(res: VerifyResult) {
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::KeyCompromised(issuer_key_thumbprint()).to_string()
+    );
+}
+
+#[test]
+fn compromised_key_accepts_credentials_issued_before_cutoff() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let first_caller_app_addr = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+
+    // The app admin marks the key as compromised for its own routes
+    set_key_compromised(
+        &mut app,
+        &contract_addr,
+        &first_caller_app_addr,
+        Some(first_caller_app_addr.to_string()),
+        None,
+    );
+
+    let before = issued_at(claims("Alice", 30, true, 2021, None), CUTOFF - 1);
+    assert!(verify(&mut app, &contract_addr, before).success);
+
+    let after = issued_at(claims("Alice", 30, true, 2021, None), CUTOFF);
+    assert_key_compromised(verify(&mut app, &contract_addr, after));
+
+    // Without `iat` the credential can not be trusted
+    assert_key_compromised(verify(
+        &mut app,
+        &contract_addr,
+        claims("Alice", 30, true, 2021, None),
+    ));
+
+    let keys: Vec<(String, KeyCompromise)> = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::GetCompromisedKeys {
+                app_addr: Some(first_caller_app_addr.to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].0, issuer_key_thumbprint());
+
+    app.execute_contract(
+        first_caller_app_addr.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::ClearKeyCompromised {
+            app_addr: Some(first_caller_app_addr.to_string()),
+            key_thumbprint: issuer_key_thumbprint(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let after = issued_at(claims("Alice", 30, true, 2021, None), CUTOFF);
+    assert!(verify(&mut app, &contract_addr, after).success);
+}
+
+#[test]
+fn compromised_key_with_idx_allow_list() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let owner = app.api().addr_make(OWNER_ADDR);
+
+    // The owner marks the key as compromised contract-wide
+    set_key_compromised(&mut app, &contract_addr, &owner, None, Some(vec![5]));
+
+    let allowed = issued_at(
+        claims_with_revocation_idx("Alice", 30, true, 2021, None, 5),
+        CUTOFF - 1,
+    );
+    assert!(verify(&mut app, &contract_addr, allowed).success);
+
+    let not_allowed = issued_at(
+        claims_with_revocation_idx("Alice", 30, true, 2021, None, 6),
+        CUTOFF - 1,
+    );
+    assert_key_compromised(verify(&mut app, &contract_addr, not_allowed));
+}
+
+#[test]
+fn compromised_key_only_set_by_app_admin_or_owner() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let first_caller_app_addr = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+    let owner = app.api().addr_make(OWNER_ADDR);
+
+    // App admins can not set contract-wide markers
+    let err = app
+        .execute_contract(
+            first_caller_app_addr.clone(),
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::SetKeyCompromised {
+                app_addr: None,
+                key_thumbprint: issuer_key_thumbprint(),
+                cutoff: Timestamp::from_seconds(CUTOFF),
+                allowed_idx: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    // Nor can the owner set markers for an app it does not administer
+    let err = app
+        .execute_contract(
+            owner,
+            contract_addr,
+            &SdjwtVerifierExecuteMsg::SetKeyCompromised {
+                app_addr: Some(first_caller_app_addr.to_string()),
+                key_thumbprint: issuer_key_thumbprint(),
+                cutoff: Timestamp::from_seconds(CUTOFF),
+                allowed_idx: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+}
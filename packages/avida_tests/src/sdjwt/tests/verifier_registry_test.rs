@@ -0,0 +1,314 @@
+use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+use cosmwasm_std::{to_json_binary, Addr, Binary};
+use cw_multi_test::{App, Executor};
+use cw_storage_plus::Item;
+
+use avida_common::types::{
+    AvidaVerifierExecuteMsg, IssuerSourceOrData, RegisterRouteRequest,
+    RouteVerificationRequirements, TrustRegistry,
+};
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    contract,
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::{InstantiateMsg, MigrateMsg, QueryMsg},
+    types::{JwkInfo, TrustedIssuer, VerifyResult},
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims, get_default_presentation_required, issuer_jwk, make_presentation, rsa_issuer_jwk,
+    ExpirationCheck, PresentationVerificationType, MAX_PRESENTATION_LEN, OWNER_ADDR,
+    SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID,
+};
+
+const REGISTRY_ENTRY: &str = "test-issuer";
+const THIRD_CALLER_APP_ADDR: &str = "addr0004";
+
+fn issuer_data(issuer: &str, jwk: &josekit::jwk::Jwk) -> Binary {
+    to_json_binary(&JwkInfo {
+        jwk: Binary::from(serde_json::to_string(jwk).unwrap().as_bytes()),
+        issuer: issuer.to_string(),
+    })
+    .unwrap()
+}
+
+fn set_trusted_issuer(app: &mut App, contract_addr: &Addr, issuer: &str) {
+    let owner = app.api().addr_make(OWNER_ADDR);
+    app.execute_contract(
+        owner,
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::SetTrustedIssuer {
+            name: REGISTRY_ENTRY.to_string(),
+            issuer_data: issuer_data(issuer, &issuer_jwk()),
+            metadata: None,
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn registry_route_requirements(name: &str) -> RouteVerificationRequirements {
+    let presentation_required = get_default_presentation_required(ExpirationCheck::NoExpiry);
+    RouteVerificationRequirements {
+        issuer_source_or_data: vec![IssuerSourceOrData {
+            source: Some(TrustRegistry::IssuerRegistry),
+            data_or_location: to_json_binary(&name).unwrap(),
+        }],
+        presentation_required: Some(Binary::from(
+            serde_json::to_string(&presentation_required)
+                .unwrap()
+                .as_bytes(),
+        )),
+    }
+}
+
+fn register_registry_route(app: &mut App, contract_addr: &Addr, app_addr: &Addr) {
+    app.execute_contract(
+        app_addr.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: app_addr.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id: SECOND_ROUTE_ID,
+                requirements: registry_route_requirements(REGISTRY_ENTRY),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn verify(app: &mut App, contract_addr: &Addr, app_addr: &Addr) -> VerifyResult {
+    let presentation = make_presentation(
+        claims("Alice", 30, true, 2021, None),
+        PresentationVerificationType::Success,
+    );
+    verify_presentation(
+        app,
+        contract_addr,
+        app_addr,
+        SECOND_ROUTE_ID,
+        &presentation,
+        None,
+    )
+}
+
+#[test]
+fn registry_update_applies_to_all_routes() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+
+    // The registry entry is for another issuer to start with
+    set_trusted_issuer(&mut app, &contract_addr, "other-issuer");
+
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    let third_app = app.api().addr_make(THIRD_CALLER_APP_ADDR);
+    register_registry_route(&mut app, &contract_addr, &second_app);
+    register_registry_route(&mut app, &contract_addr, &third_app);
+
+    for app_addr in [&second_app, &third_app] {
+        let res = verify(&mut app, &contract_addr, app_addr);
+        assert!(!res.success);
+        assert_eq!(
+            res.error.unwrap(),
+            SdjwtVerifierResultError::PubKeyNotFound.to_string()
+        );
+    }
+
+    // A single registry update applies to every route that trusts the entry
+    set_trusted_issuer(&mut app, &contract_addr, "issuer");
+
+    let entry: TrustedIssuer = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::GetTrustedIssuer {
+                name: REGISTRY_ENTRY.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(entry.issuer, "issuer");
+
+    for app_addr in [&second_app, &third_app] {
+        let res = verify(&mut app, &contract_addr, app_addr);
+        assert!(res.success);
+        assert_eq!(res.issuer.unwrap(), "issuer");
+    }
+
+    // Once removed, the routes no longer trust the issuer
+    let owner = app.api().addr_make(OWNER_ADDR);
+    app.execute_contract(
+        owner,
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::RemoveTrustedIssuer {
+            name: REGISTRY_ENTRY.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res = verify(&mut app, &contract_addr, &second_app);
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::PubKeyNotFound.to_string()
+    );
+}
+
+#[test]
+fn registry_only_managed_by_owner() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    let err = app
+        .execute_contract(
+            second_app.clone(),
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::SetTrustedIssuer {
+                name: REGISTRY_ENTRY.to_string(),
+                issuer_data: issuer_data("issuer", &issuer_jwk()),
+                metadata: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    // Ownership can be transferred
+    let owner = app.api().addr_make(OWNER_ADDR);
+    app.execute_contract(
+        owner,
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::UpdateOwner {
+            new_owner: second_app.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let new_owner: String = app
+        .wrap()
+        .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetOwner {})
+        .unwrap();
+    assert_eq!(new_owner, second_app.to_string());
+
+    // Only supported key types can be registered
+    let err = app
+        .execute_contract(
+            second_app,
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::SetTrustedIssuer {
+                name: REGISTRY_ENTRY.to_string(),
+                issuer_data: issuer_data("issuer", &rsa_issuer_jwk()),
+                metadata: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnsupportedKeyType.to_string()));
+
+    let entries: Vec<(String, TrustedIssuer)> = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr,
+            &QueryMsg::GetTrustedIssuers {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn register_unknown_registry_entry_fails() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    let err = app
+        .execute_contract(
+            second_app.clone(),
+            contract_addr,
+            &AvidaVerifierExecuteMsg::Register {
+                app_addr: second_app.to_string(),
+                requests: vec![RegisterRouteRequest {
+                    route_id: SECOND_ROUTE_ID,
+                    requirements: registry_route_requirements(REGISTRY_ENTRY),
+                }],
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(err.to_string().contains(
+        &SdjwtVerifierError::TrustedIssuerNotFound(REGISTRY_ENTRY.to_string()).to_string()
+    ));
+}
+
+#[test]
+fn migrate_sets_the_owner_of_earlier_versions() {
+    let mut deps = mock_dependencies();
+    let owner = deps.api.addr_make(OWNER_ADDR);
+    contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner, &[]),
+        InstantiateMsg {
+            max_presentation_len: MAX_PRESENTATION_LEN,
+            init_registrations: vec![],
+            cheqd_network: None,
+        },
+    )
+    .unwrap();
+
+    // Earlier versions of the contract were instantiated without an owner
+    let owner_item: Item<Addr> = Item::new("owner");
+    owner_item.remove(deps.as_mut().storage);
+
+    let err = contract::migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+    assert!(matches!(err, SdjwtVerifierError::MigrationOwnerRequired));
+
+    contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some(owner.to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(owner_item.load(deps.as_ref().storage).unwrap(), owner);
+
+    // The owner can then use the owner only messages
+    contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner, &[]),
+        SdjwtVerifierExecuteMsg::UpdateOwner {
+            new_owner: owner.to_string(),
+        }
+        .into(),
+    )
+    .unwrap();
+
+    // An existing owner is kept
+    let other = deps.api.addr_make(SECOND_CALLER_APP_ADDR);
+    contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some(other.to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(owner_item.load(deps.as_ref().storage).unwrap(), owner);
+}
@@ -0,0 +1,271 @@
+use cosmwasm_std::{Addr, StdResult};
+use cw_multi_test::{App, AppResponse, Executor};
+
+use avida_common::types::{AvidaVerifierExecuteMsg, UpdateRevocationListRequest};
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::QueryMsg,
+    types::{RevocationDelegate, VerifyResult},
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims_with_revocation_idx, get_route_requirement_with_empty_revocation_list,
+    make_presentation, PresentationVerificationType, SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID,
+};
+
+// The `iss` of the fixture presentations
+const ISSUER: &str = "issuer";
+const OTHER_ISSUER: &str = "other_issuer";
+const ISSUER_DELEGATE: &str = "issuer_delegate";
+const OTHER_ISSUER_DELEGATE: &str = "other_issuer_delegate";
+const SHARED_IDX: u64 = 7;
+
+fn revoke(
+    app: &mut App,
+    contract_addr: &Addr,
+    sender: &Addr,
+    app_addr: &Addr,
+    iss: Option<&str>,
+) -> StdResult<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::UpdateRevocationList {
+            app_addr: app_addr.to_string(),
+            request: UpdateRevocationListRequest {
+                route_id: SECOND_ROUTE_ID,
+                revoke: vec![SHARED_IDX],
+                unrevoke: vec![],
+                revoke_ranges: vec![],
+                unrevoke_ranges: vec![],
+                iss: iss.map(String::from),
+            },
+        },
+        &[],
+    )
+}
+
+fn is_revoked(app: &App, contract_addr: &Addr, app_addr: &Addr, iss: Option<&str>) -> bool {
+    app.wrap()
+        .query_wasm_smart(
+            contract_addr,
+            &QueryMsg::IsRevoked {
+                app_addr: app_addr.to_string(),
+                route_id: SECOND_ROUTE_ID,
+                idx: SHARED_IDX,
+                iss: iss.map(String::from),
+            },
+        )
+        .unwrap()
+}
+
+fn verify(app: &mut App, contract_addr: &Addr, app_addr: &Addr) -> VerifyResult {
+    let presentation = make_presentation(
+        claims_with_revocation_idx("Alice", 30, true, 2021, None, SHARED_IDX),
+        PresentationVerificationType::Success,
+    );
+    verify_presentation(
+        app,
+        contract_addr,
+        app_addr,
+        SECOND_ROUTE_ID,
+        &presentation,
+        None,
+    )
+}
+
+#[test]
+fn revocation_delegates_only_revoke_credentials_of_their_issuer() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    let issuer_delegate = app.api().addr_make(ISSUER_DELEGATE);
+    let other_issuer_delegate = app.api().addr_make(OTHER_ISSUER_DELEGATE);
+
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_app.to_string(),
+            requests: vec![get_route_requirement_with_empty_revocation_list(
+                SECOND_ROUTE_ID,
+            )],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Only the app admin can grant revocation rights
+    let err = app
+        .execute_contract(
+            issuer_delegate.clone(),
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::SetRevocationDelegate {
+                app_addr: second_app.to_string(),
+                route_id: SECOND_ROUTE_ID,
+                delegate: issuer_delegate.to_string(),
+                iss: ISSUER.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    // Non delegates cannot update the revocation list
+    let err = revoke(
+        &mut app,
+        &contract_addr,
+        &issuer_delegate,
+        &second_app,
+        None,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    for (delegate, iss) in [
+        (&issuer_delegate, ISSUER),
+        (&other_issuer_delegate, OTHER_ISSUER),
+    ] {
+        app.execute_contract(
+            second_app.clone(),
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::SetRevocationDelegate {
+                app_addr: second_app.to_string(),
+                route_id: SECOND_ROUTE_ID,
+                delegate: delegate.to_string(),
+                iss: iss.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let delegates: Vec<RevocationDelegate> = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::RevocationDelegates {
+                app_addr: second_app.to_string(),
+                route_id: SECOND_ROUTE_ID,
+            },
+        )
+        .unwrap();
+    assert_eq!(delegates.len(), 2);
+    assert!(delegates.contains(&RevocationDelegate {
+        delegate: issuer_delegate.to_string(),
+        iss: ISSUER.to_string(),
+    }));
+
+    // A delegate cannot revoke the credentials of another issuer
+    let err = revoke(
+        &mut app,
+        &contract_addr,
+        &other_issuer_delegate,
+        &second_app,
+        Some(ISSUER),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    // Revoking the same `idx` for another issuer does not revoke the credential
+    revoke(
+        &mut app,
+        &contract_addr,
+        &other_issuer_delegate,
+        &second_app,
+        None,
+    )
+    .unwrap();
+    assert!(is_revoked(
+        &app,
+        &contract_addr,
+        &second_app,
+        Some(OTHER_ISSUER)
+    ));
+    assert!(!is_revoked(&app, &contract_addr, &second_app, Some(ISSUER)));
+    assert!(!is_revoked(&app, &contract_addr, &second_app, None));
+    assert!(verify(&mut app, &contract_addr, &second_app).success);
+
+    revoke(
+        &mut app,
+        &contract_addr,
+        &issuer_delegate,
+        &second_app,
+        None,
+    )
+    .unwrap();
+    assert!(is_revoked(&app, &contract_addr, &second_app, Some(ISSUER)));
+    assert_eq!(
+        verify(&mut app, &contract_addr, &second_app).error.unwrap(),
+        SdjwtVerifierResultError::IdxRevoked(SHARED_IDX).to_string()
+    );
+
+    // Removed delegates lose their revocation rights
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::RemoveRevocationDelegate {
+            app_addr: second_app.to_string(),
+            route_id: SECOND_ROUTE_ID,
+            delegate: issuer_delegate.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    let err = revoke(
+        &mut app,
+        &contract_addr,
+        &issuer_delegate,
+        &second_app,
+        None,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+}
+
+#[test]
+fn app_admin_revocations_apply_to_all_issuers() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_app.to_string(),
+            requests: vec![get_route_requirement_with_empty_revocation_list(
+                SECOND_ROUTE_ID,
+            )],
+        },
+        &[],
+    )
+    .unwrap();
+
+    revoke(&mut app, &contract_addr, &second_app, &second_app, None).unwrap();
+
+    assert!(is_revoked(&app, &contract_addr, &second_app, None));
+    assert!(is_revoked(&app, &contract_addr, &second_app, Some(ISSUER)));
+    assert!(is_revoked(
+        &app,
+        &contract_addr,
+        &second_app,
+        Some(OTHER_ISSUER)
+    ));
+    assert_eq!(
+        verify(&mut app, &contract_addr, &second_app).error.unwrap(),
+        SdjwtVerifierResultError::IdxRevoked(SHARED_IDX).to_string()
+    );
+}
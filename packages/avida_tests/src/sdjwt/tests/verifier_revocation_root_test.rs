@@ -0,0 +1,185 @@
+use cosmwasm_std::{Addr, Binary, StdResult};
+use cw_multi_test::{App, AppResponse, Executor};
+
+use avida_common::types::{AvidaVerifierExecuteMsg, RegisterRouteRequest};
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::QueryMsg,
+    types::{Criterion, ReqAttr, RevocationRoot, SmtNonMembershipProof, VerifyResult, IDX},
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims_with_revocation_idx, make_presentation, make_revocation_root_and_proof,
+    make_route_verification_requirements, KeyType, PresentationVerificationType,
+    FIRST_CALLER_APP_ADDR, SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID,
+};
+
+const REVOKED_IDX: u64 = 111;
+const VALID_IDX: u64 = 7;
+
+fn register_revocation_root_route(app: &mut App, contract_addr: &Addr) -> Addr {
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_app.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id: SECOND_ROUTE_ID,
+                requirements: make_route_verification_requirements(
+                    vec![ReqAttr {
+                        attribute: IDX.to_string(),
+                        criterion: Criterion::NotInRevocationRoot,
+                    }],
+                    KeyType::Ed25519,
+                ),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+    second_app
+}
+
+fn update_revocation_root(
+    app: &mut App,
+    contract_addr: &Addr,
+    sender: &Addr,
+    root: Binary,
+) -> StdResult<AppResponse> {
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    app.execute_contract(
+        sender.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::UpdateRevocationRoot {
+            app_addr: second_app.to_string(),
+            route_id: SECOND_ROUTE_ID,
+            root,
+        },
+        &[],
+    )
+}
+
+fn verify(
+    app: &mut App,
+    contract_addr: &Addr,
+    idx: u64,
+    proof: Option<&SmtNonMembershipProof>,
+) -> VerifyResult {
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    let presentation = make_presentation(
+        claims_with_revocation_idx("Alice", 30, true, 2021, None, idx),
+        PresentationVerificationType::Success,
+    );
+    verify_presentation(
+        app,
+        contract_addr,
+        &second_app,
+        SECOND_ROUTE_ID,
+        &presentation,
+        proof,
+    )
+}
+
+#[test]
+fn non_membership_proof_against_committed_root() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = register_revocation_root_route(&mut app, &contract_addr);
+
+    let (root, proof) = make_revocation_root_and_proof(&[5, REVOKED_IDX], VALID_IDX);
+
+    // Nothing can be verified before a root is committed
+    let res = verify(&mut app, &contract_addr, VALID_IDX, Some(&proof));
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::RevocationRootNotCommitted.to_string()
+    );
+
+    update_revocation_root(&mut app, &contract_addr, &second_app, root.clone()).unwrap();
+
+    let res = verify(&mut app, &contract_addr, VALID_IDX, Some(&proof));
+    assert!(res.success);
+
+    let res = verify(&mut app, &contract_addr, VALID_IDX, None);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::RevocationProofNotProvided.to_string()
+    );
+
+    // A revoked idx has no valid non-membership proof
+    let (_, proof) = make_revocation_root_and_proof(&[5, REVOKED_IDX], REVOKED_IDX);
+    let res = verify(&mut app, &contract_addr, REVOKED_IDX, Some(&proof));
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::RevocationProofRootMismatch(REVOKED_IDX).to_string()
+    );
+
+    let roots: Vec<RevocationRoot> = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr,
+            &QueryMsg::GetRevocationRoots {
+                app_addr: second_app.to_string(),
+                route_id: SECOND_ROUTE_ID,
+            },
+        )
+        .unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].root, root);
+}
+
+#[test]
+fn proofs_against_replaced_root_verify_within_grace_period() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = register_revocation_root_route(&mut app, &contract_addr);
+
+    let (root, proof) = make_revocation_root_and_proof(&[5], REVOKED_IDX);
+    update_revocation_root(&mut app, &contract_addr, &second_app, root).unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(60));
+    let (root, _) = make_revocation_root_and_proof(&[5, REVOKED_IDX], REVOKED_IDX);
+    update_revocation_root(&mut app, &contract_addr, &second_app, root).unwrap();
+
+    // The proof was made just before the update
+    let res = verify(&mut app, &contract_addr, REVOKED_IDX, Some(&proof));
+    assert!(res.success);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(601));
+    let res = verify(&mut app, &contract_addr, REVOKED_IDX, Some(&proof));
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::RevocationProofRootMismatch(REVOKED_IDX).to_string()
+    );
+}
+
+#[test]
+fn revocation_root_only_updated_by_app_admin() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = register_revocation_root_route(&mut app, &contract_addr);
+    let first_app = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+
+    let (root, _) = make_revocation_root_and_proof(&[REVOKED_IDX], VALID_IDX);
+    let err = update_revocation_root(&mut app, &contract_addr, &first_app, root).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    let err = update_revocation_root(
+        &mut app,
+        &contract_addr,
+        &second_app,
+        Binary::from(vec![1u8; 31]),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::RevocationRootInvalid.to_string()));
+}
@@ -0,0 +1,390 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::{App, Executor};
+use cw_storage_plus::{Item, Map};
+use serde_json::json;
+
+use avida_common::types::{
+    AvidaVerifierExecuteMsg, IdxRange, RegisterRouteRequest, RouteId, UpdateRevocationListRequest,
+};
+use avida_sdjwt_verifier::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::{MigrateMsg, QueryMsg},
+    types::{Criterion, ReqAttr, VerificationRequirements, VerifyResult, IDX},
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims_with_revocation_idx, make_presentation, make_route_verification_requirements, KeyType,
+    PresentationVerificationType, OWNER_ADDR, SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID,
+};
+
+const REVOKED_IDX: u64 = 111;
+// Revoked `idx` are stored as bitmaps in chunks of 2048
+const REVOKED_IDX_OTHER_CHUNK: u64 = 5000;
+
+fn route_with_revocation_list(revocation_list: Vec<u64>) -> RegisterRouteRequest {
+    RegisterRouteRequest {
+        route_id: SECOND_ROUTE_ID,
+        requirements: make_route_verification_requirements(
+            vec![ReqAttr {
+                attribute: IDX.to_string(),
+                criterion: Criterion::NotContainedIn(revocation_list),
+            }],
+            KeyType::Ed25519,
+        ),
+    }
+}
+
+fn verify_idx(app: &mut App, contract_addr: &Addr, app_addr: &Addr, idx: u64) -> VerifyResult {
+    let presentation = make_presentation(
+        claims_with_revocation_idx("Alice", 30, true, 2021, None, idx),
+        PresentationVerificationType::Success,
+    );
+    verify_presentation(
+        app,
+        contract_addr,
+        app_addr,
+        SECOND_ROUTE_ID,
+        &presentation,
+        None,
+    )
+}
+
+fn assert_revoked(app: &mut App, contract_addr: &Addr, app_addr: &Addr, idx: u64) {
+    let res = verify_idx(app, contract_addr, app_addr, idx);
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::IdxRevoked(idx).to_string()
+    );
+}
+
+fn inline_revocation_list(app: &App, contract_addr: &Addr, app_addr: &Addr) -> Criterion {
+    let req: VerificationRequirements = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr,
+            &QueryMsg::GetRouteRequirements {
+                app_addr: app_addr.to_string(),
+                route_id: SECOND_ROUTE_ID,
+            },
+        )
+        .unwrap();
+    req.presentation_required
+        .into_iter()
+        .find(|req| req.attribute == IDX)
+        .unwrap()
+        .criterion
+}
+
+#[test]
+fn route_revocation_list_follows_register_and_update() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_app.to_string(),
+            requests: vec![route_with_revocation_list(vec![
+                REVOKED_IDX,
+                REVOKED_IDX_OTHER_CHUNK,
+            ])],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The revocations are stored outside of the requirements and returned with them
+    assert_eq!(
+        inline_revocation_list(&app, &contract_addr, &second_app),
+        Criterion::NotContainedIn(vec![REVOKED_IDX, REVOKED_IDX_OTHER_CHUNK])
+    );
+    assert_revoked(&mut app, &contract_addr, &second_app, REVOKED_IDX);
+    assert_revoked(
+        &mut app,
+        &contract_addr,
+        &second_app,
+        REVOKED_IDX_OTHER_CHUNK,
+    );
+    assert!(verify_idx(&mut app, &contract_addr, &second_app, REVOKED_IDX + 1).success);
+
+    // The revocation list of updated requirements replaces the route revocations
+    let update = |app: &mut App, revocation_list: Vec<u64>| {
+        app.execute_contract(
+            second_app.clone(),
+            contract_addr.clone(),
+            &AvidaVerifierExecuteMsg::Update {
+                app_addr: second_app.to_string(),
+                route_id: SECOND_ROUTE_ID,
+                route_criteria: Some(route_with_revocation_list(revocation_list).requirements),
+            },
+            &[],
+        )
+        .unwrap();
+    };
+    update(&mut app, vec![REVOKED_IDX]);
+
+    assert_eq!(
+        inline_revocation_list(&app, &contract_addr, &second_app),
+        Criterion::NotContainedIn(vec![REVOKED_IDX])
+    );
+    assert_revoked(&mut app, &contract_addr, &second_app, REVOKED_IDX);
+    assert!(
+        verify_idx(
+            &mut app,
+            &contract_addr,
+            &second_app,
+            REVOKED_IDX_OTHER_CHUNK
+        )
+        .success
+    );
+
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::UpdateRevocationList {
+            app_addr: second_app.to_string(),
+            request: UpdateRevocationListRequest {
+                route_id: SECOND_ROUTE_ID,
+                revoke: vec![REVOKED_IDX_OTHER_CHUNK],
+                unrevoke: vec![],
+                revoke_ranges: vec![],
+                unrevoke_ranges: vec![],
+                iss: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        inline_revocation_list(&app, &contract_addr, &second_app),
+        Criterion::NotContainedIn(vec![REVOKED_IDX, REVOKED_IDX_OTHER_CHUNK])
+    );
+
+    // An empty list unrevokes all the credentials of the route
+    update(&mut app, vec![]);
+    assert!(verify_idx(&mut app, &contract_addr, &second_app, REVOKED_IDX).success);
+    assert!(
+        verify_idx(
+            &mut app,
+            &contract_addr,
+            &second_app,
+            REVOKED_IDX_OTHER_CHUNK
+        )
+        .success
+    );
+}
+
+#[test]
+fn migrate_moves_revocation_lists_out_of_requirements() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_app.to_string(),
+            requests: vec![route_with_revocation_list(vec![])],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Earlier versions of the contract kept the revocation list in the route requirements
+    let routes: Map<(String, RouteId), VerificationRequirements> =
+        Map::new("app_routes_requirements");
+    let contract_info: Item<serde_json::Value> = Item::new("contract_info");
+    let key = (second_app.to_string(), SECOND_ROUTE_ID);
+    {
+        let mut storage = app.contract_storage_mut(&contract_addr);
+        let mut requirements = routes.load(storage.as_ref(), key.clone()).unwrap();
+        requirements.presentation_required[0].criterion =
+            Criterion::NotContainedIn(vec![REVOKED_IDX]);
+        routes
+            .save(storage.as_mut(), key.clone(), &requirements)
+            .unwrap();
+        contract_info
+            .save(
+                storage.as_mut(),
+                &json!({"contract": "avida-sdjwt-verifier", "version": "0.1.0"}),
+            )
+            .unwrap();
+    }
+
+    let owner = app.api().addr_make(OWNER_ADDR);
+    let code_id = app.contract_data(&contract_addr).unwrap().code_id;
+    let migrate = |app: &mut App| {
+        app.migrate_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &MigrateMsg { owner: None },
+            code_id,
+        )
+        .unwrap()
+        .events
+        .into_iter()
+        .flat_map(|event| event.attributes)
+        .find(|attr| attr.key == "migrated_routes")
+        .unwrap()
+        .value
+    };
+    assert_eq!(migrate(&mut app), "1");
+
+    let requirements = routes
+        .load(app.contract_storage(&contract_addr).as_ref(), key)
+        .unwrap();
+    assert_eq!(
+        requirements.presentation_required[0].criterion,
+        Criterion::NotContainedIn(vec![])
+    );
+    assert_eq!(
+        inline_revocation_list(&app, &contract_addr, &second_app),
+        Criterion::NotContainedIn(vec![REVOKED_IDX])
+    );
+    assert_revoked(&mut app, &contract_addr, &second_app, REVOKED_IDX);
+
+    // The routes are only migrated from the earlier versions
+    assert_eq!(migrate(&mut app), "0");
+    assert_revoked(&mut app, &contract_addr, &second_app, REVOKED_IDX);
+}
+
+fn is_revoked(app: &App, contract_addr: &Addr, app_addr: &Addr, idx: u64) -> bool {
+    app.wrap()
+        .query_wasm_smart(
+            contract_addr,
+            &QueryMsg::IsRevoked {
+                app_addr: app_addr.to_string(),
+                route_id: SECOND_ROUTE_ID,
+                idx,
+                iss: None,
+            },
+        )
+        .unwrap()
+}
+
+fn revocation_list(
+    app: &App,
+    contract_addr: &Addr,
+    app_addr: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Vec<u64> {
+    app.wrap()
+        .query_wasm_smart(
+            contract_addr,
+            &QueryMsg::RevocationList {
+                app_addr: app_addr.to_string(),
+                route_id: SECOND_ROUTE_ID,
+                iss: None,
+                start_after,
+                limit,
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn revoke_ranges_and_query_revocation_list() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_app.to_string(),
+            requests: vec![route_with_revocation_list(vec![])],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // A whole batch is revoked at once, except for a single credential
+    app.execute_contract(
+        second_app.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::UpdateRevocationList {
+            app_addr: second_app.to_string(),
+            request: UpdateRevocationListRequest {
+                route_id: SECOND_ROUTE_ID,
+                revoke: vec![5],
+                unrevoke: vec![11_000],
+                revoke_ranges: vec![IdxRange {
+                    start: 10_000,
+                    end: 12_000,
+                }],
+                unrevoke_ranges: vec![],
+                iss: None,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    for (idx, revoked) in [
+        (5, true),
+        (9_999, false),
+        (10_000, true),
+        (11_000, false),
+        (12_000, true),
+        (12_001, false),
+    ] {
+        assert_eq!(is_revoked(&app, &contract_addr, &second_app, idx), revoked);
+    }
+
+    assert_eq!(
+        revocation_list(&app, &contract_addr, &second_app, None, Some(3)),
+        vec![5, 10_000, 10_001]
+    );
+    assert_eq!(
+        revocation_list(&app, &contract_addr, &second_app, Some(10_999), Some(2)),
+        vec![11_001, 11_002]
+    );
+    assert_eq!(
+        revocation_list(&app, &contract_addr, &second_app, Some(11_999), None),
+        vec![12_000]
+    );
+
+    // Ranges must be ordered and updates bounded
+    for (range, err) in [
+        (
+            IdxRange { start: 2, end: 1 },
+            SdjwtVerifierError::RevocationRangeInvalid(2, 1),
+        ),
+        (
+            IdxRange {
+                start: 0,
+                end: u64::MAX,
+            },
+            SdjwtVerifierError::RevocationUpdateTooLarge(1 << 20),
+        ),
+    ] {
+        let res = app.execute_contract(
+            second_app.clone(),
+            contract_addr.clone(),
+            &AvidaVerifierExecuteMsg::UpdateRevocationList {
+                app_addr: second_app.to_string(),
+                request: UpdateRevocationListRequest {
+                    route_id: SECOND_ROUTE_ID,
+                    revoke: vec![],
+                    unrevoke: vec![],
+                    revoke_ranges: vec![range],
+                    unrevoke_ranges: vec![],
+                    iss: None,
+                },
+            },
+            &[],
+        );
+        assert!(res.unwrap_err().to_string().contains(&err.to_string()));
+    }
+}
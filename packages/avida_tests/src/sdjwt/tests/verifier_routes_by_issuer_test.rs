@@ -0,0 +1,110 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::{App, Executor};
+
+use avida_common::types::{AvidaVerifierExecuteMsg, RouteId};
+use avida_sdjwt_verifier::msg::QueryMsg;
+
+use super::fixtures::default_instantiate_verifier_contract;
+use crate::sdjwt::fixtures::{
+    get_two_input_routes_requirements, FIRST_CALLER_APP_ADDR, FIRST_ROUTE_ID, OWNER_ADDR,
+    SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID, THIRD_ROUTE_ID,
+};
+
+fn routes_by_issuer(
+    app: &App,
+    contract_addr: &Addr,
+    iss: &str,
+    start_after: Option<(String, RouteId)>,
+    limit: Option<u32>,
+) -> Vec<(String, RouteId)> {
+    app.wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::RoutesByIssuer {
+                iss: iss.to_string(),
+                start_after,
+                limit,
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn routes_by_issuer_follows_route_changes() {
+    let mut app = App::default();
+
+    // Instantiate verifier contract with a route trusting `issuer`
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+
+    let owner = app.api().addr_make(OWNER_ADDR);
+    let first_caller_app_addr = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    // Register another app with two routes trusting the same issuer
+    app.execute_contract(
+        owner.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_caller_app_addr.to_string(),
+            requests: get_two_input_routes_requirements(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let routes = routes_by_issuer(&app, &contract_addr, "issuer", None, None);
+    assert_eq!(routes.len(), 3);
+    for route in [
+        (first_caller_app_addr.to_string(), FIRST_ROUTE_ID),
+        (second_caller_app_addr.to_string(), SECOND_ROUTE_ID),
+        (second_caller_app_addr.to_string(), THIRD_ROUTE_ID),
+    ] {
+        assert!(routes.contains(&route));
+    }
+    assert!(routes_by_issuer(&app, &contract_addr, "other-issuer", None, None).is_empty());
+
+    // Paginate through the routes
+    let first_page = routes_by_issuer(&app, &contract_addr, "issuer", None, Some(2));
+    assert_eq!(first_page, routes[..2]);
+    let second_page = routes_by_issuer(
+        &app,
+        &contract_addr,
+        "issuer",
+        first_page.last().cloned(),
+        Some(2),
+    );
+    assert_eq!(second_page, routes[2..]);
+
+    // Removing a route removes it from the index
+    app.execute_contract(
+        owner.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Update {
+            app_addr: second_caller_app_addr.to_string(),
+            route_id: SECOND_ROUTE_ID,
+            route_criteria: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let routes = routes_by_issuer(&app, &contract_addr, "issuer", None, None);
+    assert_eq!(routes.len(), 2);
+    assert!(!routes.contains(&(second_caller_app_addr.to_string(), SECOND_ROUTE_ID)));
+
+    // Deregistering the app removes all its routes from the index
+    app.execute_contract(
+        owner,
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Deregister {
+            app_addr: second_caller_app_addr.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        routes_by_issuer(&app, &contract_addr, "issuer", None, None),
+        vec![(first_caller_app_addr.to_string(), FIRST_ROUTE_ID)]
+    );
+}
@@ -0,0 +1,230 @@
+use cosmwasm_std::{Addr, StdResult};
+use cw_multi_test::{App, AppResponse, Executor};
+
+use avida_common::types::{AvidaVerifierExecuteMsg, RegisterRouteRequest};
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::QueryMsg,
+    types::{Criterion, ReqAttr, SharedRevocationList, VerifyResult, IDX},
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims_with_revocation_idx, make_presentation, make_route_verification_requirements, KeyType,
+    PresentationVerificationType, SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID, THIRD_ROUTE_ID,
+};
+
+const LIST_ID: &str = "issuer-revocations";
+const LIST_OWNER: &str = "list_owner";
+const LIST_EDITOR: &str = "list_editor";
+const THIRD_CALLER_APP_ADDR: &str = "addr0004";
+const REVOKED_IDX: u64 = 42;
+
+fn routes_with_shared_list() -> Vec<RegisterRouteRequest> {
+    [SECOND_ROUTE_ID, THIRD_ROUTE_ID]
+        .into_iter()
+        .map(|route_id| RegisterRouteRequest {
+            route_id,
+            requirements: make_route_verification_requirements(
+                vec![ReqAttr {
+                    attribute: IDX.to_string(),
+                    criterion: Criterion::NotInRevocationList(LIST_ID.to_string()),
+                }],
+                KeyType::Ed25519,
+            ),
+        })
+        .collect()
+}
+
+fn register(app: &mut App, contract_addr: &Addr, app_addr: &Addr) -> StdResult<AppResponse> {
+    app.execute_contract(
+        app_addr.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: app_addr.to_string(),
+            requests: routes_with_shared_list(),
+        },
+        &[],
+    )
+}
+
+fn update_list(
+    app: &mut App,
+    contract_addr: &Addr,
+    sender: &Addr,
+    revoke: Vec<u64>,
+    unrevoke: Vec<u64>,
+) -> StdResult<AppResponse> {
+    app.execute_contract(
+        sender.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::UpdateSharedRevocationList {
+            list_id: LIST_ID.to_string(),
+            revoke,
+            unrevoke,
+            revoke_ranges: vec![],
+            unrevoke_ranges: vec![],
+        },
+        &[],
+    )
+}
+
+fn verify(app: &mut App, contract_addr: &Addr, app_addr: &Addr, route_id: u64) -> VerifyResult {
+    let presentation = make_presentation(
+        claims_with_revocation_idx("Alice", 30, true, 2021, None, REVOKED_IDX),
+        PresentationVerificationType::Success,
+    );
+    verify_presentation(app, contract_addr, app_addr, route_id, &presentation, None)
+}
+
+#[test]
+fn shared_revocation_list_update_applies_to_all_routes() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_app = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    let third_app = app.api().addr_make(THIRD_CALLER_APP_ADDR);
+    let list_owner = app.api().addr_make(LIST_OWNER);
+    let list_editor = app.api().addr_make(LIST_EDITOR);
+
+    // Routes can only reference existing lists
+    let err = register(&mut app, &contract_addr, &second_app).unwrap_err();
+    assert!(err.to_string().contains(
+        &SdjwtVerifierError::SharedRevocationListNotFound(LIST_ID.to_string()).to_string()
+    ));
+
+    app.execute_contract(
+        list_owner.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::CreateSharedRevocationList {
+            list_id: LIST_ID.to_string(),
+            editors: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // List ids are unique
+    let err = app
+        .execute_contract(
+            second_app.clone(),
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::CreateSharedRevocationList {
+                list_id: LIST_ID.to_string(),
+                editors: vec![],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains(
+        &SdjwtVerifierError::SharedRevocationListAlreadyExists(LIST_ID.to_string()).to_string()
+    ));
+
+    for app_addr in [&second_app, &third_app] {
+        register(&mut app, &contract_addr, app_addr).unwrap();
+        for route_id in [SECOND_ROUTE_ID, THIRD_ROUTE_ID] {
+            assert!(verify(&mut app, &contract_addr, app_addr, route_id).success);
+        }
+    }
+
+    // Only the owner and editors can update the list
+    let err = update_list(
+        &mut app,
+        &contract_addr,
+        &list_editor,
+        vec![REVOKED_IDX],
+        vec![],
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    app.execute_contract(
+        list_owner.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::UpdateSharedRevocationListAcl {
+            list_id: LIST_ID.to_string(),
+            new_owner: None,
+            add_editors: vec![list_editor.to_string()],
+            remove_editors: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let list: SharedRevocationList = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::GetSharedRevocationList {
+                list_id: LIST_ID.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        list,
+        SharedRevocationList {
+            owner: list_owner.clone(),
+            editors: vec![list_editor.clone()],
+        }
+    );
+
+    // A single update revokes the credential on all the routes of all the apps
+    update_list(
+        &mut app,
+        &contract_addr,
+        &list_editor,
+        vec![REVOKED_IDX],
+        vec![],
+    )
+    .unwrap();
+
+    for app_addr in [&second_app, &third_app] {
+        for route_id in [SECOND_ROUTE_ID, THIRD_ROUTE_ID] {
+            assert_eq!(
+                verify(&mut app, &contract_addr, app_addr, route_id)
+                    .error
+                    .unwrap(),
+                SdjwtVerifierResultError::IdxRevoked(REVOKED_IDX).to_string()
+            );
+        }
+    }
+
+    let is_revoked: bool = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::IsRevokedInSharedList {
+                list_id: LIST_ID.to_string(),
+                idx: REVOKED_IDX,
+            },
+        )
+        .unwrap();
+    assert!(is_revoked);
+    let revoked: Vec<u64> = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::SharedRevocationListEntries {
+                list_id: LIST_ID.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(revoked, vec![REVOKED_IDX]);
+
+    update_list(
+        &mut app,
+        &contract_addr,
+        &list_owner,
+        vec![],
+        vec![REVOKED_IDX],
+    )
+    .unwrap();
+    for app_addr in [&second_app, &third_app] {
+        assert!(verify(&mut app, &contract_addr, app_addr, SECOND_ROUTE_ID).success);
+    }
+}
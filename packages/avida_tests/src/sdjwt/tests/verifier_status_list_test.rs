@@ -0,0 +1,316 @@
+use cosmwasm_std::{Addr, Binary, StdResult};
+use cw_multi_test::{App, Executor};
+use serde_json::Value;
+
+use avida_common::types::{
+    AvidaVerifierExecuteMsg, IssuerSourceOrData, RegisterRouteRequest, TrustRegistry,
+};
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::QueryMsg,
+    types::{
+        Criterion, PresentationReq, ReqAttr, StatusList, StatusListCriterion, VerifyResult,
+        STATUS_KEY,
+    },
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims, make_presentation, make_route_verification_requirements, make_status_list_token,
+    KeyType, PresentationVerificationType, FIRST_CALLER_APP_ADDR, FIRST_ROUTE_ID,
+    SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID,
+};
+
+const STATUS_LIST_URI: &str = "https://example.com/statuslists/1";
+
+// 2-bit statuses, idx 0: VALID, idx 1: INVALID, idx 2: SUSPENDED, idx 3: 0x03
+const STATUS_LIST: [u8; 1] = [0b1110_0100];
+
+fn register_status_list_route(app: &mut App, contract_addr: &Addr, accepted_statuses: Vec<u8>) {
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    let presentation_req: PresentationReq = vec![ReqAttr {
+        attribute: STATUS_KEY.to_string(),
+        criterion: Criterion::StatusList(StatusListCriterion { accepted_statuses }),
+    }];
+    app.execute_contract(
+        second_caller_app_addr.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_caller_app_addr.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id: SECOND_ROUTE_ID,
+                requirements: make_route_verification_requirements(
+                    presentation_req,
+                    KeyType::Ed25519,
+                ),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn update_status_list(
+    app: &mut App,
+    contract_addr: &Addr,
+    sender: &Addr,
+    status_list: IssuerSourceOrData,
+) -> StdResult<()> {
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    app.execute_contract(
+        sender.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::UpdateStatusList {
+            app_addr: second_caller_app_addr.to_string(),
+            route_id: SECOND_ROUTE_ID,
+            status_list,
+        },
+        &[],
+    )
+    .map(|_| ())
+}
+
+fn push_status_list(app: &mut App, contract_addr: &Addr, token: String) -> StdResult<()> {
+    // Anyone can push a list signed by the issuer
+    let relayer = app.api().addr_make("relayer");
+    update_status_list(
+        app,
+        contract_addr,
+        &relayer,
+        IssuerSourceOrData {
+            source: None,
+            data_or_location: Binary::from(token.as_bytes()),
+        },
+    )
+}
+
+fn query_status_list<T: serde::de::DeserializeOwned>(
+    app: &App,
+    contract_addr: &Addr,
+    app_addr: &str,
+    route_id: u64,
+) -> T {
+    app.wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::GetStatusList {
+                app_addr: app.api().addr_make(app_addr).to_string(),
+                route_id,
+                iss: "issuer".to_string(),
+                uri: STATUS_LIST_URI.to_string(),
+            },
+        )
+        .unwrap()
+}
+
+fn verify(app: &mut App, contract_addr: &Addr, idx: u64) -> VerifyResult {
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    let mut claims = claims("Alice", 30, true, 2021, None);
+    claims[STATUS_KEY] = serde_json::json!({
+        "status_list": { "idx": idx, "uri": STATUS_LIST_URI }
+    });
+    let presentation = make_presentation(claims, PresentationVerificationType::Success);
+    verify_presentation(
+        app,
+        contract_addr,
+        &second_caller_app_addr,
+        SECOND_ROUTE_ID,
+        &presentation,
+        None,
+    )
+}
+
+#[test]
+fn status_list_statuses_are_checked() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    register_status_list_route(&mut app, &contract_addr, vec![]);
+
+    // The list has to be stored before the credentials can be verified
+    let res = verify(&mut app, &contract_addr, 0);
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::StatusListNotFound(STATUS_LIST_URI.to_string()).to_string()
+    );
+
+    push_status_list(
+        &mut app,
+        &contract_addr,
+        make_status_list_token(STATUS_LIST_URI, 1000, 2, &STATUS_LIST),
+    )
+    .unwrap();
+
+    let stored: StatusList = query_status_list(
+        &app,
+        &contract_addr,
+        SECOND_CALLER_APP_ADDR,
+        SECOND_ROUTE_ID,
+    );
+    assert_eq!(stored.bits, 2);
+    assert_eq!(stored.lst, Binary::from(STATUS_LIST));
+
+    assert!(verify(&mut app, &contract_addr, 0).success);
+
+    for (idx, error) in [
+        (1, SdjwtVerifierResultError::StatusInvalid(1)),
+        (2, SdjwtVerifierResultError::StatusSuspended(2)),
+        (3, SdjwtVerifierResultError::StatusNotAccepted(3, 3)),
+        (4, SdjwtVerifierResultError::StatusIdxOutOfRange(4)),
+    ] {
+        let res = verify(&mut app, &contract_addr, idx);
+        assert!(!res.success);
+        assert_eq!(res.error.unwrap(), error.to_string());
+    }
+
+    // An updated list reinstates the suspended credential
+    push_status_list(
+        &mut app,
+        &contract_addr,
+        make_status_list_token(STATUS_LIST_URI, 1001, 2, &[0b1100_0100]),
+    )
+    .unwrap();
+    assert!(verify(&mut app, &contract_addr, 2).success);
+}
+
+#[test]
+fn status_list_accepted_statuses() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    // The route accepts suspended credentials
+    register_status_list_route(&mut app, &contract_addr, vec![0x02]);
+
+    push_status_list(
+        &mut app,
+        &contract_addr,
+        make_status_list_token(STATUS_LIST_URI, 1000, 2, &STATUS_LIST),
+    )
+    .unwrap();
+
+    assert!(verify(&mut app, &contract_addr, 2).success);
+    assert!(!verify(&mut app, &contract_addr, 1).success);
+}
+
+#[test]
+fn status_list_update_rejects_older_or_unsigned_lists() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    register_status_list_route(&mut app, &contract_addr, vec![]);
+
+    push_status_list(
+        &mut app,
+        &contract_addr,
+        make_status_list_token(STATUS_LIST_URI, 1000, 2, &STATUS_LIST),
+    )
+    .unwrap();
+
+    let err = push_status_list(
+        &mut app,
+        &contract_addr,
+        make_status_list_token(STATUS_LIST_URI, 999, 2, &[0]),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::StatusListOutdated.to_string()));
+
+    // A list with the same `iat` does not replace the stored one either
+    let err = push_status_list(
+        &mut app,
+        &contract_addr,
+        make_status_list_token(STATUS_LIST_URI, 1000, 2, &[0]),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::StatusListOutdated.to_string()));
+
+    // Tampering with the payload breaks the signature
+    let token = make_status_list_token(STATUS_LIST_URI, 1001, 2, &[0]);
+    let mut parts: Vec<&str> = token.split('.').collect();
+    let other = make_status_list_token(STATUS_LIST_URI, 1001, 2, &[0xff]);
+    parts[1] = other.split('.').nth(1).unwrap();
+    let err = push_status_list(&mut app, &contract_addr, parts.join(".")).unwrap_err();
+    assert!(err.to_string().contains("Status list token invalid"));
+
+    // Fetching from a trust registry is up to the app admin
+    let relayer = app.api().addr_make("relayer");
+    let err = update_status_list(
+        &mut app,
+        &contract_addr,
+        &relayer,
+        IssuerSourceOrData {
+            source: Some(TrustRegistry::Cheqd),
+            data_or_location: Binary::default(),
+        },
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    let stored: Value = query_status_list(
+        &app,
+        &contract_addr,
+        SECOND_CALLER_APP_ADDR,
+        SECOND_ROUTE_ID,
+    );
+    assert_eq!(stored["iat"], 1000);
+}
+
+#[test]
+fn status_list_of_another_route_is_not_overwritten() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    register_status_list_route(&mut app, &contract_addr, vec![]);
+
+    push_status_list(
+        &mut app,
+        &contract_addr,
+        make_status_list_token(STATUS_LIST_URI, 1000, 2, &STATUS_LIST),
+    )
+    .unwrap();
+
+    // Another app trusting a key for the same `iss` pushes a newer list with all statuses valid
+    let first_caller_app_addr = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+    app.execute_contract(
+        first_caller_app_addr.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::UpdateStatusList {
+            app_addr: first_caller_app_addr.to_string(),
+            route_id: FIRST_ROUTE_ID,
+            status_list: IssuerSourceOrData {
+                source: None,
+                data_or_location: Binary::from(
+                    make_status_list_token(STATUS_LIST_URI, 2000, 2, &[0]).as_bytes(),
+                ),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The list is only stored for its own route
+    let stored: Value =
+        query_status_list(&app, &contract_addr, FIRST_CALLER_APP_ADDR, FIRST_ROUTE_ID);
+    assert_eq!(stored["iat"], 2000);
+    let stored: Value = query_status_list(
+        &app,
+        &contract_addr,
+        SECOND_CALLER_APP_ADDR,
+        SECOND_ROUTE_ID,
+    );
+    assert_eq!(stored["iat"], 1000);
+
+    let res = verify(&mut app, &contract_addr, 1);
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::StatusInvalid(1).to_string()
+    );
+}
@@ -0,0 +1,172 @@
+use cosmwasm_std::Addr;
+use cw_multi_test::{App, Executor};
+
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::{
+    errors::{SdjwtVerifierError, SdjwtVerifierResultError},
+    msg::QueryMsg,
+    types::{IssuerSuspension, VerifyResult},
+};
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims, issuer_key_thumbprint, make_presentation, PresentationVerificationType,
+    FIRST_CALLER_APP_ADDR, FIRST_ROUTE_ID, OWNER_ADDR,
+};
+
+fn verify(app: &mut App, contract_addr: &Addr) -> VerifyResult {
+    let first_caller_app_addr = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+    let presentation = make_presentation(
+        claims("Alice", 30, true, 2021, None),
+        PresentationVerificationType::Success,
+    );
+    verify_presentation(
+        app,
+        contract_addr,
+        &first_caller_app_addr,
+        FIRST_ROUTE_ID,
+        &presentation,
+        None,
+    )
+}
+
+#[test]
+fn suspended_issuer_fails_verification_until_reinstated() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let owner = app.api().addr_make(OWNER_ADDR);
+
+    assert!(verify(&mut app, &contract_addr).success);
+
+    app.execute_contract(
+        owner.clone(),
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::SuspendIssuer {
+            iss: Some("issuer".to_string()),
+            key_thumbprint: None,
+            reason: Some("compromised".to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The route still trusts the issuer, but the suspension applies to all routes
+    let res = verify(&mut app, &contract_addr);
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::IssuerSuspended("issuer".to_string()).to_string()
+    );
+
+    let suspended: Vec<(String, IssuerSuspension)> = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::GetSuspendedIssuers {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(suspended.len(), 1);
+    assert_eq!(suspended[0].0, "issuer");
+    assert_eq!(suspended[0].1.reason, Some("compromised".to_string()));
+
+    app.execute_contract(
+        owner,
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::ReinstateIssuer {
+            iss: Some("issuer".to_string()),
+            key_thumbprint: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert!(verify(&mut app, &contract_addr).success);
+}
+
+#[test]
+fn suspended_key_fails_verification() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let owner = app.api().addr_make(OWNER_ADDR);
+
+    app.execute_contract(
+        owner,
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::SuspendIssuer {
+            iss: None,
+            key_thumbprint: Some(issuer_key_thumbprint()),
+            reason: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res = verify(&mut app, &contract_addr);
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::IssuerSuspended(issuer_key_thumbprint()).to_string()
+    );
+}
+
+#[test]
+fn suspension_only_managed_by_owner() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let first_caller_app_addr = app.api().addr_make(FIRST_CALLER_APP_ADDR);
+    let owner = app.api().addr_make(OWNER_ADDR);
+
+    let err = app
+        .execute_contract(
+            first_caller_app_addr,
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::SuspendIssuer {
+                iss: Some("issuer".to_string()),
+                key_thumbprint: None,
+                reason: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::UnauthorisedCaller.to_string()));
+
+    // A target must be given
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &SdjwtVerifierExecuteMsg::SuspendIssuer {
+                iss: None,
+                key_thumbprint: None,
+                reason: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::SuspensionTargetNotProvided.to_string()));
+
+    let err = app
+        .execute_contract(
+            owner,
+            contract_addr,
+            &SdjwtVerifierExecuteMsg::ReinstateIssuer {
+                iss: Some("issuer".to_string()),
+                key_thumbprint: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::IssuerNotSuspended("issuer".to_string()).to_string()));
+}
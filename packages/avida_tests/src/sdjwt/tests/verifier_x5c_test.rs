@@ -0,0 +1,245 @@
+use cosmwasm_std::{Binary, Timestamp};
+use cw_multi_test::{App, Executor};
+
+use avida_common::types::{
+    AvidaVerifierExecuteMsg, IssuerSourceOrData, RegisterRouteRequest,
+    RouteVerificationRequirements, TrustRegistry,
+};
+use avida_sdjwt_verifier::errors::{SdjwtVerifierError, SdjwtVerifierResultError};
+use avida_sdjwt_verifier::msg::SdjwtVerifierExecuteMsg;
+use avida_sdjwt_verifier::types::VerifyResult;
+
+use super::fixtures::{default_instantiate_verifier_contract, verify_presentation};
+use crate::sdjwt::fixtures::{
+    claims, get_default_presentation_required, make_presentation, make_x5c_presentation,
+    x509_cert_b64, ExpirationCheck, PresentationVerificationType, OWNER_ADDR,
+    SECOND_CALLER_APP_ADDR, SECOND_ROUTE_ID,
+};
+
+const ROOT_CERT: &str = "test_x509_root.pem";
+const LEAF_CERT: &str = "test_x509_leaf.pem";
+/// An expired root with the same subject as `ROOT_CERT` and another key
+const EXPIRED_ROOT_CERT: &str = "test_x509_expired_root.pem";
+
+fn x509_route_requirements(root_cert: &str) -> RouteVerificationRequirements {
+    let presentation_required = get_default_presentation_required(ExpirationCheck::NoExpiry);
+    RouteVerificationRequirements {
+        issuer_source_or_data: vec![IssuerSourceOrData {
+            source: Some(TrustRegistry::X509),
+            data_or_location: Binary::from_base64(&x509_cert_b64(root_cert)).unwrap(),
+        }],
+        presentation_required: Some(Binary::from(
+            serde_json::to_string(&presentation_required)
+                .unwrap()
+                .as_bytes(),
+        )),
+    }
+}
+
+fn register_x509_route(app: &mut App, contract_addr: &cosmwasm_std::Addr) {
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    app.execute_contract(
+        second_caller_app_addr.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_caller_app_addr.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id: SECOND_ROUTE_ID,
+                requirements: x509_route_requirements(ROOT_CERT),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn verify(app: &mut App, contract_addr: &cosmwasm_std::Addr, presentation: String) -> VerifyResult {
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+    verify_presentation(
+        app,
+        contract_addr,
+        &second_caller_app_addr,
+        SECOND_ROUTE_ID,
+        &presentation,
+        None,
+    )
+}
+
+#[test]
+fn x5c_chain_to_registered_root_verifies() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    register_x509_route(&mut app, &contract_addr);
+
+    // The chain may or may not include the root itself
+    for x5c in [
+        vec![x509_cert_b64(LEAF_CERT)],
+        vec![x509_cert_b64(LEAF_CERT), x509_cert_b64(ROOT_CERT)],
+    ] {
+        let presentation = make_x5c_presentation(claims("Alice", 30, true, 2021, None), x5c);
+        let res = verify(&mut app, &contract_addr, presentation);
+
+        assert!(res.success);
+        // The leaf subject is the effective issuer
+        assert_eq!(res.issuer.unwrap(), "CN=issuer");
+    }
+}
+
+#[test]
+fn x5c_chain_anchors_to_any_root_with_the_issuer_subject() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    // The expired root, which did not sign the chain, is registered before its renewal
+    let mut requirements = x509_route_requirements(ROOT_CERT);
+    requirements.issuer_source_or_data.insert(
+        0,
+        IssuerSourceOrData {
+            source: Some(TrustRegistry::X509),
+            data_or_location: Binary::from_base64(&x509_cert_b64(EXPIRED_ROOT_CERT)).unwrap(),
+        },
+    );
+    app.execute_contract(
+        second_caller_app_addr.clone(),
+        contract_addr.clone(),
+        &AvidaVerifierExecuteMsg::Register {
+            app_addr: second_caller_app_addr.to_string(),
+            requests: vec![RegisterRouteRequest {
+                route_id: SECOND_ROUTE_ID,
+                requirements,
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let presentation = make_x5c_presentation(
+        claims("Alice", 30, true, 2021, None),
+        vec![x509_cert_b64(LEAF_CERT)],
+    );
+    let res = verify(&mut app, &contract_addr, presentation);
+
+    assert!(res.success);
+    assert_eq!(res.issuer.unwrap(), "CN=issuer");
+}
+
+#[test]
+fn x5c_chain_outside_validity_period_fails() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    register_x509_route(&mut app, &contract_addr);
+
+    // The fixture certificates expire on 2049-01-01
+    app.update_block(|block| block.time = Timestamp::from_seconds(2_524_608_000));
+
+    let presentation = make_x5c_presentation(
+        claims("Alice", 30, true, 2021, None),
+        vec![x509_cert_b64(LEAF_CERT)],
+    );
+    let res = verify(&mut app, &contract_addr, presentation);
+
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::X5cCertificateNotValid("CN=issuer".to_string()).to_string()
+    );
+}
+
+#[test]
+fn x5c_chain_required_on_root_only_route() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    register_x509_route(&mut app, &contract_addr);
+
+    // Without `x5c` the issuer key has to be registered as a JWK
+    let presentation = make_presentation(
+        claims("Alice", 30, true, 2021, None),
+        PresentationVerificationType::Success,
+    );
+    let res = verify(&mut app, &contract_addr, presentation);
+
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::PubKeyNotFound.to_string()
+    );
+
+    // The root certificate alone can not sign the credential
+    let presentation = make_x5c_presentation(
+        claims("Alice", 30, true, 2021, None),
+        vec![x509_cert_b64(ROOT_CERT)],
+    );
+    let res = verify(&mut app, &contract_addr, presentation);
+
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::X5cKeyUsage("CN=AVIDA Test Root CA".to_string()).to_string()
+    );
+}
+
+#[test]
+fn register_non_ca_root_fails() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    let second_caller_app_addr = app.api().addr_make(SECOND_CALLER_APP_ADDR);
+
+    let err = app
+        .execute_contract(
+            second_caller_app_addr.clone(),
+            contract_addr,
+            &AvidaVerifierExecuteMsg::Register {
+                app_addr: second_caller_app_addr.to_string(),
+                requests: vec![RegisterRouteRequest {
+                    route_id: SECOND_ROUTE_ID,
+                    requirements: x509_route_requirements(LEAF_CERT),
+                }],
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains(&SdjwtVerifierError::InvalidRootCertificate(String::new()).to_string()));
+}
+
+#[test]
+fn suspended_x5c_issuer_fails_verification() {
+    let mut app = App::default();
+
+    let (contract_addr, _) = default_instantiate_verifier_contract(&mut app);
+    register_x509_route(&mut app, &contract_addr);
+    let owner = app.api().addr_make(OWNER_ADDR);
+
+    // The leaf subject is suspended as the effective issuer
+    app.execute_contract(
+        owner,
+        contract_addr.clone(),
+        &SdjwtVerifierExecuteMsg::SuspendIssuer {
+            iss: Some("CN=issuer".to_string()),
+            key_thumbprint: None,
+            reason: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let presentation = make_x5c_presentation(
+        claims("Alice", 30, true, 2021, None),
+        vec![x509_cert_b64(LEAF_CERT)],
+    );
+    let res = verify(&mut app, &contract_addr, presentation);
+
+    assert!(!res.success);
+    assert_eq!(
+        res.error.unwrap(),
+        SdjwtVerifierResultError::IssuerSuspended("CN=issuer".to_string()).to_string()
+    );
+}
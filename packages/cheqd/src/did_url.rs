@@ -0,0 +1,212 @@
+use std::{fmt, str::FromStr};
+
+use cosmwasm_schema::cw_serde;
+
+use crate::{
+    ibc::ResourceError,
+    types::{ResourceReqPacket, ResourceRequirements},
+};
+
+const DID_CHEQD_PREFIX: &str = "did:cheqd:";
+const RESOURCES_PATH: &str = "/resources/";
+
+/// The cheqd network of a DID, `mainnet` when the DID has no namespace
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum CheqdNetwork {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
+impl FromStr for CheqdNetwork {
+    type Err = ResourceError;
+
+    fn from_str(network: &str) -> Result<Self, Self::Err> {
+        match network {
+            "mainnet" => Ok(CheqdNetwork::Mainnet),
+            "testnet" => Ok(CheqdNetwork::Testnet),
+            _ => Err(ResourceError::UnknownNetwork(network.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for CheqdNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheqdNetwork::Mainnet => write!(f, "mainnet"),
+            CheqdNetwork::Testnet => write!(f, "testnet"),
+        }
+    }
+}
+
+/// A DID-Linked Resource URL, either by path,
+/// `did:cheqd:testnet:<id>/resources/<resource id>`,
+/// or by query, `did:cheqd:testnet:<id>?resourceName=<name>&resourceType=<type>`
+#[cw_serde]
+pub struct DidResourceUrl {
+    pub network: CheqdNetwork,
+    /// The unique identifier of the DID, which is the resource `collection_id`
+    pub collection_id: String,
+    pub resource_id: Option<String>,
+    pub resource_name: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_version: Option<String>,
+}
+
+impl DidResourceUrl {
+    /// The resource request of the URL.
+    /// The cheqd resource module only serves resources by id over IBC,
+    /// so the URL must have a resource id
+    pub fn resource_req(&self) -> Result<ResourceReqPacket, ResourceError> {
+        let resource_id = self
+            .resource_id
+            .clone()
+            .ok_or_else(|| ResourceError::ResourceIdMissing(self.to_string()))?;
+        Ok(ResourceReqPacket {
+            resource_id,
+            collection_id: self.collection_id.clone(),
+        })
+    }
+
+    /// The name and type of the URL query, to be checked on the fetched resource
+    pub fn requirements(&self) -> ResourceRequirements {
+        ResourceRequirements {
+            resource_name: self.resource_name.clone(),
+            resource_type: self.resource_type.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl FromStr for DidResourceUrl {
+    type Err = ResourceError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| ResourceError::InvalidDidUrl(format!("{url}: {reason}"));
+
+        let rest = url
+            .strip_prefix(DID_CHEQD_PREFIX)
+            .ok_or_else(|| invalid("not a did:cheqd URL"))?;
+        if rest.contains('#') {
+            return Err(invalid("fragments are not supported"));
+        }
+        let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let (did, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, ""),
+        };
+
+        let (network, collection_id) = match did.split_once(':') {
+            Some((network, id)) => (network.parse()?, id),
+            None => (CheqdNetwork::Mainnet, did),
+        };
+        if !is_identifier(collection_id) {
+            return Err(invalid("invalid DID identifier"));
+        }
+
+        let mut resource_id = match path {
+            "" => None,
+            _ => Some(
+                path.strip_prefix(RESOURCES_PATH)
+                    .filter(|id| is_identifier(id))
+                    .ok_or_else(|| invalid("the path must be /resources/<resource id>"))?
+                    .to_string(),
+            ),
+        };
+
+        let (mut resource_name, mut resource_type, mut resource_version) = (None, None, None);
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param
+                .split_once('=')
+                .ok_or_else(|| invalid("query parameters must have a value"))?;
+            let value = percent_decode(value).ok_or_else(|| invalid("invalid percent encoding"))?;
+            let field = match key {
+                "resourceId" => &mut resource_id,
+                "resourceName" => &mut resource_name,
+                "resourceType" => &mut resource_type,
+                "resourceVersion" => &mut resource_version,
+                _ => return Err(invalid(&format!("unsupported query parameter {key}"))),
+            };
+            if field.replace(value).is_some() {
+                return Err(invalid(&format!("{key} is set more than once")));
+            }
+        }
+
+        if resource_id.as_deref().is_some_and(|id| !is_identifier(id)) {
+            return Err(invalid("invalid resource id"));
+        }
+        if resource_id.is_none() && resource_name.is_none() {
+            return Err(invalid("a resource id or name is required"));
+        }
+
+        Ok(DidResourceUrl {
+            network,
+            collection_id: collection_id.to_string(),
+            resource_id,
+            resource_name,
+            resource_type,
+            resource_version,
+        })
+    }
+}
+
+impl fmt::Display for DidResourceUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{DID_CHEQD_PREFIX}{}:{}",
+            self.network, self.collection_id
+        )?;
+        if let Some(resource_id) = &self.resource_id {
+            write!(f, "{RESOURCES_PATH}{resource_id}")?;
+        }
+        let params = [
+            ("resourceName", &self.resource_name),
+            ("resourceType", &self.resource_type),
+            ("resourceVersion", &self.resource_version),
+        ];
+        let mut separator = '?';
+        for (key, value) in params {
+            if let Some(value) = value {
+                write!(f, "{separator}{key}={}", percent_encode(value))?;
+                separator = '&';
+            }
+        }
+        Ok(())
+    }
+}
+
+/// DID and resource identifiers are UUIDs or base58 strings
+fn is_identifier(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
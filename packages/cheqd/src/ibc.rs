@@ -2,8 +2,9 @@ use cosmwasm_std::{
     from_json, Env, Ibc3ChannelOpenResponse, IbcBasicResponse, IbcChannel, IbcChannelOpenMsg,
     IbcChannelOpenResponse, IbcOrder, IbcPacketAckMsg, StdAck, StdError, StdResult, Timestamp,
 };
+use sha2::{Digest, Sha256};
 
-use crate::types::{ResourceReqPacket, ResourceWithMetadata};
+use crate::types::{ResourceReqPacket, ResourceRequirements, ResourceWithMetadata};
 use thiserror::Error;
 
 /// This is the same as the cheqd resource module IBC version
@@ -24,6 +25,51 @@ pub enum ChannelError {
     InvalidPort,
 }
 
+#[derive(Error, Debug, PartialEq)]
+pub enum ResourceError {
+    #[error("Ack Returned Err: {0}")]
+    AckError(String),
+
+    #[error("Ack data invalid: {0}")]
+    InvalidAck(String),
+
+    #[error("Ack Returned Unmatched resource_id")]
+    UnmatchedResourceId,
+
+    #[error("Ack Returned Unmatched collection_id")]
+    UnmatchedCollectionId,
+
+    #[error("Resource checksum missing")]
+    ChecksumMissing,
+
+    #[error("Resource checksum {expected} does not match the data checksum {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Resource media type {actual:?} is not {expected}")]
+    UnexpectedMediaType {
+        expected: String,
+        actual: Option<String>,
+    },
+
+    #[error("Resource type {actual} is not {expected}")]
+    UnexpectedResourceType { expected: String, actual: String },
+
+    #[error("Invalid RFC 3339 time: {0}")]
+    InvalidTime(String),
+
+    #[error("Resource name {actual} is not {expected}")]
+    UnexpectedResourceName { expected: String, actual: String },
+
+    #[error("Invalid DID-Linked Resource URL {0}")]
+    InvalidDidUrl(String),
+
+    #[error("Unknown cheqd network {0}")]
+    UnknownNetwork(String),
+
+    #[error("DID-Linked Resource URL {0} has no resource id")]
+    ResourceIdMissing(String),
+}
+
 pub fn check_order(order: &IbcOrder) -> Result<(), ChannelError> {
     if order != &APP_ORDER {
         Err(ChannelError::InvalidChannelOrder)
@@ -81,26 +127,53 @@ pub fn ibc_channel_close_handler() -> StdResult<IbcBasicResponse> {
     Err(StdError::msg("Closing is not supported"))
 }
 
-/// Checks ack resource matching requested resource
+/// Checks ack resource matching requested resource,
+/// its checksum (the SHA-256 of the data) and the `requirements` on its metadata
 pub fn ibc_packet_ack_resource_extractor(
     msg: IbcPacketAckMsg,
-) -> StdResult<(ResourceReqPacket, ResourceWithMetadata)> {
-    let ack: StdAck = from_json(&msg.acknowledgement.data)?;
+    requirements: &ResourceRequirements,
+) -> Result<(ResourceReqPacket, ResourceWithMetadata), ResourceError> {
+    let ack: StdAck = from_json(&msg.acknowledgement.data)
+        .map_err(|e| ResourceError::InvalidAck(e.to_string()))?;
     match ack {
         StdAck::Success(binary) => {
-            let resource: ResourceWithMetadata = from_json(binary)?;
-            let original_packet: ResourceReqPacket = from_json(&msg.original_packet.data)?;
-
-            if original_packet.resource_id != resource.linked_resource_metadata.resource_id {
-                Err(StdError::msg("Ack Returned Unmatched resource_id"))
-            } else if original_packet.collection_id
-                != resource.linked_resource_metadata.resource_collection_id
-            {
-                Err(StdError::msg("Ack Returned Unmatched collection_id"))
-            } else {
-                Ok((original_packet, resource))
+            let resource: ResourceWithMetadata =
+                from_json(binary).map_err(|e| ResourceError::InvalidAck(e.to_string()))?;
+            let original_packet: ResourceReqPacket = from_json(&msg.original_packet.data)
+                .map_err(|e| ResourceError::InvalidAck(e.to_string()))?;
+            let metadata = &resource.linked_resource_metadata;
+
+            if original_packet.resource_id != metadata.resource_id {
+                return Err(ResourceError::UnmatchedResourceId);
+            }
+            if original_packet.collection_id != metadata.resource_collection_id {
+                return Err(ResourceError::UnmatchedCollectionId);
             }
+
+            let expected = metadata
+                .checksum
+                .as_ref()
+                .ok_or(ResourceError::ChecksumMissing)?;
+            let actual = resource_checksum(&resource.linked_resource.data);
+            if !expected.eq_ignore_ascii_case(&actual) {
+                return Err(ResourceError::ChecksumMismatch {
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+
+            requirements.check(metadata)?;
+
+            Ok((original_packet, resource))
         }
-        StdAck::Error(err) => Err(StdError::msg(format!("Ack Returned Err: {}", err))),
+        StdAck::Error(err) => Err(ResourceError::AckError(err)),
     }
 }
+
+/// The checksum of a resource as computed by cheqd, the hex encoded SHA-256 of its data
+pub fn resource_checksum(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
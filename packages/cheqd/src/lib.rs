@@ -1,2 +1,4 @@
+pub mod did_url;
 pub mod ibc;
+pub mod time;
 pub mod types;
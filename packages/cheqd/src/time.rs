@@ -0,0 +1,115 @@
+use cosmwasm_std::Timestamp;
+
+use crate::ibc::ResourceError;
+
+/// Parses an RFC 3339 date-time, such as the `created` time of a cheqd resource,
+/// e.g. `2024-01-01T00:00:00Z` or `2024-01-01T01:00:00.5+01:00`.
+/// Times before the unix epoch are not supported
+pub fn parse_rfc3339(time: &str) -> Result<Timestamp, ResourceError> {
+    let invalid = || ResourceError::InvalidTime(time.to_string());
+    let bytes = time.as_bytes();
+    if !time.is_ascii()
+        || bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return Err(invalid());
+    }
+
+    let number = |digits: &str| -> Result<u64, ResourceError> {
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            digits.parse().map_err(|_| invalid())
+        } else {
+            Err(invalid())
+        }
+    };
+    let (year, month, day) = (
+        number(&time[0..4])?,
+        number(&time[5..7])?,
+        number(&time[8..10])?,
+    );
+    let (hour, minute, second) = (
+        number(&time[11..13])?,
+        number(&time[14..16])?,
+        number(&time[17..19])?,
+    );
+
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        // Leap seconds are allowed
+        || second > 60
+    {
+        return Err(invalid());
+    }
+
+    // Fraction of a second, only the nanoseconds are kept
+    let mut rest = &time[19..];
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        nanos = fraction[..digits]
+            .bytes()
+            .chain(std::iter::repeat(b'0'))
+            .take(9)
+            .fold(0, |nanos, digit| nanos * 10 + u64::from(digit - b'0'));
+        rest = &fraction[digits..];
+    }
+
+    let offset_secs: i64 = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return Err(invalid()),
+            };
+            let (offset_hour, offset_minute) = (number(&rest[1..3])?, number(&rest[4..6])?);
+            if offset_hour > 23 || offset_minute > 59 {
+                return Err(invalid());
+            }
+            sign * (offset_hour * 3600 + offset_minute * 60) as i64
+        }
+        _ => return Err(invalid()),
+    };
+
+    let secs = days_from_civil(year, month, day) * 86400
+        + (hour * 3600 + minute * 60 + second) as i64
+        - offset_secs;
+    let secs = u64::try_from(secs).map_err(|_| invalid())?;
+
+    Ok(Timestamp::from_seconds(secs).plus_nanos(nanos))
+}
+
+fn is_leap_year(year: u64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since the unix epoch of a (proleptic Gregorian) date
+fn days_from_civil(year: u64, month: u64, day: u64) -> i64 {
+    // The year starts in March, so that the leap day is the last day of the year
+    let year = year as i64 - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
@@ -1,7 +1,9 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_json_binary, Binary, IbcChannel};
+use cosmwasm_std::{to_json_binary, Binary, IbcChannel, Timestamp};
 use cw_storage_plus::Item;
 
+use crate::{ibc::ResourceError, time::parse_rfc3339};
+
 /// This is set for the verifier to prevent the presentation from being too large
 pub type Channel = Item<IbcChannel>;
 pub const CHANNEL: Channel = Item::new("mpl");
@@ -19,6 +21,52 @@ impl std::fmt::Display for ResourceReqPacket {
     }
 }
 
+/// Requirements on a fetched resource, besides matching the requested resource
+#[cw_serde]
+#[derive(Default)]
+pub struct ResourceRequirements {
+    /// The required `media_type`, e.g. `application/json`
+    #[serde(default)]
+    pub media_type: Option<String>,
+    /// The required `resource_type`
+    #[serde(default)]
+    pub resource_type: Option<String>,
+    /// The required `resource_name`
+    #[serde(default)]
+    pub resource_name: Option<String>,
+}
+
+impl ResourceRequirements {
+    /// Checks the metadata of a fetched resource against the requirements
+    pub fn check(&self, metadata: &LinkedResourceMetadata) -> Result<(), ResourceError> {
+        if let Some(media_type) = &self.media_type {
+            if metadata.media_type.as_ref() != Some(media_type) {
+                return Err(ResourceError::UnexpectedMediaType {
+                    expected: media_type.clone(),
+                    actual: metadata.media_type.clone(),
+                });
+            }
+        }
+        if let Some(resource_name) = &self.resource_name {
+            if &metadata.resource_name != resource_name {
+                return Err(ResourceError::UnexpectedResourceName {
+                    expected: resource_name.clone(),
+                    actual: metadata.resource_name.clone(),
+                });
+            }
+        }
+        if let Some(resource_type) = &self.resource_type {
+            if &metadata.resource_type != resource_type {
+                return Err(ResourceError::UnexpectedResourceType {
+                    expected: resource_type.clone(),
+                    actual: metadata.resource_type.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cw_serde]
 #[serde(rename_all = "camelCase")]
 /// This is the same type as the one stored on cheqd
@@ -100,6 +148,13 @@ pub struct LinkedResourceMetadata {
     pub next_version_id: Option<String>,
 }
 
+impl LinkedResourceMetadata {
+    /// The time the resource was created on cheqd
+    pub fn created_at(&self) -> Result<Timestamp, ResourceError> {
+        parse_rfc3339(&self.created)
+    }
+}
+
 #[cw_serde]
 pub struct AlternativeUri {
     pub uri: Option<String>,
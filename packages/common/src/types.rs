@@ -21,6 +21,31 @@ pub struct UpdateRevocationListRequest {
     pub route_id: u64,
     pub revoke: Vec<u64>,
     pub unrevoke: Vec<u64>,
+    /// Ranges of `idx` revoked at once, e.g. a whole batch of credentials
+    #[serde(default)]
+    pub revoke_ranges: Vec<IdxRange>,
+    #[serde(default)]
+    pub unrevoke_ranges: Vec<IdxRange>,
+    /// The issuer whose credentials are updated, `None` updates the revocations of all the issuers.
+    /// Revocation delegates can only update the credentials of the issuer they were granted
+    #[serde(default)]
+    pub iss: Option<String>,
+}
+
+/// An inclusive range of credential `idx`
+#[cw_serde]
+pub struct IdxRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl From<u64> for IdxRange {
+    fn from(idx: u64) -> Self {
+        IdxRange {
+            start: idx,
+            end: idx,
+        }
+    }
 }
 
 /// Specific verification requirements for the route, by `route_id`
@@ -37,6 +62,12 @@ pub struct RouteVerificationRequirements {
 #[cw_serde]
 pub enum TrustRegistry {
     Cheqd = 1,
+    /// X.509 PKI, the data is a DER encoded root CA certificate used as a trust anchor
+    /// for the `x5c` certificate chain in the presentation header
+    X509 = 2,
+    /// The contract-wide trusted issuer registry of the verifier,
+    /// the data is the name of the registry entry
+    IssuerRegistry = 3,
 }
 
 /// Location to obtain the verification data from
@@ -46,6 +77,8 @@ pub struct IssuerSourceOrData {
     pub source: Option<TrustRegistry>,
     /// The data or location of the verification data at the trust registry
     /// For TrustRegistry::Cheqd, it is the `ResourceReqPacket` in avida-cheqd
+    /// For TrustRegistry::X509, it is the DER encoded root CA certificate
+    /// For TrustRegistry::IssuerRegistry, it is the json string of the registry entry name
     /// For data, the contracts should have the expected type
     /// In Sdjwt-Verifier, this is expected to be the JwkInfo struct
     pub data_or_location: Binary,